@@ -2,17 +2,15 @@ use factors::{calculate_population_density, create_terrain};
 use graphics::write_to_image;
 use map_provider::MapProvider;
 use naturalneighbor::Interpolator;
-use random::RandomF64;
 use rules_provider::road::RulesProviderForRoad;
 use street_engine::{
     core::geometry::site::Site,
-    transport::{builder::TransportBuilder, params::numeric::Stage},
+    transport::{builder::TransportBuilder, params::numeric::Stage, random::Pcg32},
 };
 
 mod factors;
 mod graphics;
 mod map_provider;
-mod random;
 mod rules_provider;
 
 fn main() {
@@ -55,7 +53,7 @@ fn main() {
     let rules_provider_road = RulesProviderForRoad::new(&map_provider);
     //let rules_provider_railway = RulesProviderForRailway::new(&map_provider);
 
-    let mut rnd = RandomF64::new();
+    let mut rnd = Pcg32::new(0);
 
     let network = TransportBuilder::new(&rules_provider_road, &map_provider, &rules_provider_road)
         .add_origin(Site { x: 0.0, y: 0.0 }, 0.0, Some(Stage::from_num(0)))
@@ -35,7 +35,7 @@ impl<'a> MapProvider<'a> {
     pub fn get_population_density(&self, site: &Site) -> Option<f64> {
         self.interpolator
             .interpolate(
-                &self.population_densities,
+                self.population_densities,
                 naturalneighbor::Point {
                     x: site.x,
                     y: site.y,
@@ -51,6 +51,6 @@ impl<'a> TerrainProvider for MapProvider<'a> {
         if elevation < 1e-1 {
             return None;
         }
-        return Some(elevation);
+        Some(elevation)
     }
 }
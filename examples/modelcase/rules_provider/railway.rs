@@ -1,25 +1,24 @@
-use street_engine::{
-    core::geometry::site::Site,
-    transport::{
-        params::{
-            metrics::PathMetrics,
-            numeric::Stage,
-            priority::PathPrioritizationFactors,
-            rules::{
-                branch::BranchRules, bridge::BridgeRules, direction::PathDirectionRules,
-                ElevationDiffLimit, TransportRules,
-            },
+use street_engine::transport::{
+    context::GrowthContext,
+    params::{
+        priority::PathPrioritizationFactors,
+        rules::{
+            branch::BranchRules, bridge::BridgeRules, clearance::ClearanceRules,
+            direction::PathDirectionRules, isoline::IsolineRules, junction::JunctionRules,
+            switchback::SwitchbackRules, ElevationDiffLimit, TransportRules,
         },
-        traits::{PathPrioritizator, TransportRulesProvider},
     },
+    traits::{PathPrioritizator, TransportRulesProvider},
 };
 
 use crate::map_provider::{into_fastlem_site, MapProvider};
 
+#[allow(dead_code)]
 pub struct RulesProviderForRailway<'a> {
     map_provider: &'a MapProvider<'a>,
 }
 
+#[allow(dead_code)]
 impl RulesProviderForRailway<'_> {
     pub fn new<'a>(map_provider: &'a MapProvider<'a>) -> RulesProviderForRailway<'a> {
         RulesProviderForRailway { map_provider }
@@ -27,11 +26,12 @@ impl RulesProviderForRailway<'_> {
 }
 
 impl<'a> TransportRulesProvider for RulesProviderForRailway<'a> {
-    fn get_rules(&self, site: &Site, _: Stage, metrics: &PathMetrics) -> Option<TransportRules> {
+    fn get_rules(&self, context: &GrowthContext) -> Option<TransportRules> {
+        let (site, metrics) = (&context.node.site, context.metrics);
         let population_density = self.map_provider.get_population_density(site)?;
         let path_normal_length = 0.7;
 
-        let branch_motivation = if metrics.extend_count_since_last_branched % 7 == 0
+        let branch_motivation = if metrics.extend_count_since_last_branched.is_multiple_of(7)
             && metrics.extend_count_since_last_branched > 7
         {
             1.0
@@ -51,11 +51,16 @@ impl<'a> TransportRulesProvider for RulesProviderForRailway<'a> {
             path_direction_rules: PathDirectionRules {
                 max_radian: std::f64::consts::PI / (10.0 + 50.0 * population_density),
                 comparison_step: 3,
+                contour_affinity: 0.0,
             },
             bridge_rules: BridgeRules {
                 max_bridge_length: 8.0,
                 check_step: 3,
             },
+            switchback_rules: SwitchbackRules::default(),
+            isoline_rules: IsolineRules::default(),
+            junction_rules: JunctionRules::default(),
+            clearance_rules: ClearanceRules::default(),
         })
     }
 }
@@ -1,17 +1,14 @@
-use street_engine::{
-    core::geometry::site::Site,
-    transport::{
-        params::{
-            metrics::PathMetrics,
-            numeric::Stage,
-            priority::PathPrioritizationFactors,
-            rules::{
-                branch::BranchRules, bridge::BridgeRules, direction::PathDirectionRules,
-                ElevationDiffLimit, TransportRules,
-            },
+use street_engine::transport::{
+    context::GrowthContext,
+    params::{
+        priority::PathPrioritizationFactors,
+        rules::{
+            branch::BranchRules, bridge::BridgeRules, clearance::ClearanceRules,
+            direction::PathDirectionRules, isoline::IsolineRules, junction::JunctionRules,
+            switchback::SwitchbackRules, ElevationDiffLimit, TransportRules,
         },
-        traits::{PathPrioritizator, TransportRulesProvider},
     },
+    traits::{PathPrioritizator, TransportRulesProvider},
 };
 
 use crate::map_provider::{into_fastlem_site, MapProvider};
@@ -27,16 +24,12 @@ impl RulesProviderForRoad<'_> {
 }
 
 impl<'a> TransportRulesProvider for RulesProviderForRoad<'a> {
-    fn get_rules(
-        &self,
-        site: &Site,
-        stage: Stage,
-        metrics: &PathMetrics,
-    ) -> Option<TransportRules> {
+    fn get_rules(&self, context: &GrowthContext) -> Option<TransportRules> {
+        let (site, stage, metrics) = (&context.node.site, context.stage, context.metrics);
         let population_density = self.map_provider.get_population_density(site)?;
         let is_street = stage.as_num() > 0;
 
-        let path_normal_length = if metrics.branch_count % 2 == 0 {
+        let path_normal_length = if metrics.branch_count.is_multiple_of(2) {
             0.35
         } else {
             0.45
@@ -59,8 +52,19 @@ impl<'a> TransportRulesProvider for RulesProviderForRoad<'a> {
                 path_direction_rules: PathDirectionRules {
                     max_radian: std::f64::consts::PI / (5.0 + 1000.0 * population_density),
                     comparison_step: 3,
+                    contour_affinity: 0.0,
                 },
                 bridge_rules: BridgeRules::default(),
+                switchback_rules: SwitchbackRules::default(),
+                isoline_rules: IsolineRules::default(),
+                junction_rules: JunctionRules {
+                    min_angle: std::f64::consts::PI / 6.0,
+                    min_intersection_spacing: path_normal_length * 0.3,
+                },
+                clearance_rules: ClearanceRules {
+                    min_parallel_clearance: path_normal_length * 0.5,
+                    parallel_max_radian: std::f64::consts::PI / 8.0,
+                },
             })
         } else {
             // highway
@@ -76,11 +80,23 @@ impl<'a> TransportRulesProvider for RulesProviderForRoad<'a> {
                 path_direction_rules: PathDirectionRules {
                     max_radian: std::f64::consts::PI / (10.0 + 100.0 * population_density),
                     comparison_step: 3,
+                    contour_affinity: 0.0,
                 },
                 bridge_rules: BridgeRules {
                     max_bridge_length: 25.0,
                     check_step: 15,
                 },
+                switchback_rules: SwitchbackRules {
+                    max_hairpins: 6,
+                    min_hairpin_spacing: path_normal_length * 3.0,
+                    check_step: 4,
+                },
+                isoline_rules: IsolineRules::default(),
+                junction_rules: JunctionRules {
+                    min_angle: std::f64::consts::PI / 4.0,
+                    min_intersection_spacing: path_normal_length,
+                },
+                clearance_rules: ClearanceRules::default(),
             })
         }
     }
@@ -99,9 +115,9 @@ impl<'a> PathPrioritizator for RulesProviderForRoad<'a> {
 
         let stage = factor.stage;
         if stage.as_num() > 0 {
-            return Some(path_priority);
+            Some(path_priority)
         } else {
-            return Some(path_priority + 1e5);
+            Some(path_priority + 1e5)
         }
     }
 }
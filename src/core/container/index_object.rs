@@ -61,7 +61,7 @@ where
 
             let d0 = start.distance(&Site::new(point[0], point[1]));
             let d1 = end.distance(&Site::new(point[0], point[1]));
-            d0.min(d1)
+            d0.min(d1).powi(2)
         }
     }
 }
@@ -131,3 +131,22 @@ where
         self.node_id == other.node_id
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_path_distance_2() {
+        let path = PathTreeObject::new(
+            LineSegment::new(Site::new(0.0, 0.0), Site::new(1.0, 0.0)),
+            (0, 1),
+        );
+
+        // Projected onto the segment.
+        assert!((path.distance_2(&[0.5, 2.0]) - 4.0).abs() < 1e-6);
+        // Nearest to an endpoint: must also be the squared distance.
+        assert!((path.distance_2(&[3.0, 0.0]) - 4.0).abs() < 1e-6);
+        assert!((path.distance_2(&[-3.0, 4.0]) - 25.0).abs() < 1e-6);
+    }
+}
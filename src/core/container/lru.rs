@@ -0,0 +1,92 @@
+use std::collections::BTreeMap;
+
+/// Cache which evicts the least recently used entry when the capacity is exceeded.
+#[derive(Debug, Clone)]
+pub struct LruCache<K, V>
+where
+    K: Ord + Clone,
+    V: Clone,
+{
+    capacity: usize,
+    /// value and the time of the last use of each key.
+    entries: BTreeMap<K, (V, u64)>,
+    /// keys ordered by the time of the last use.
+    recency: BTreeMap<u64, K>,
+    clock: u64,
+}
+
+impl<K, V> LruCache<K, V>
+where
+    K: Ord + Clone,
+    V: Clone,
+{
+    /// Create a new cache holding at most `capacity` entries.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: BTreeMap::new(),
+            recency: BTreeMap::new(),
+            clock: 0,
+        }
+    }
+
+    /// Get the value of the key and mark it as the most recently used.
+    pub fn get(&mut self, key: &K) -> Option<V> {
+        let (value, last_used) = self.entries.get_mut(key)?;
+        self.recency.remove(last_used);
+        self.clock += 1;
+        *last_used = self.clock;
+        self.recency.insert(self.clock, key.clone());
+        Some(value.clone())
+    }
+
+    /// Insert the value of the key, evicting the least recently used entry if the cache is full.
+    pub fn insert(&mut self, key: K, value: V) {
+        if self.capacity == 0 {
+            return;
+        }
+        self.clock += 1;
+        if let Some((_, last_used)) = self.entries.insert(key.clone(), (value, self.clock)) {
+            self.recency.remove(&last_used);
+        }
+        self.recency.insert(self.clock, key);
+        while self.entries.len() > self.capacity {
+            if let Some((_, key)) = self.recency.pop_first() {
+                self.entries.remove(&key);
+            }
+        }
+    }
+
+    /// Remove all entries.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.recency.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_eviction() {
+        let mut cache = LruCache::new(2);
+        cache.insert(0, "a");
+        cache.insert(1, "b");
+        assert_eq!(cache.get(&0), Some("a"));
+        // 1 is the least recently used.
+        cache.insert(2, "c");
+        assert_eq!(cache.get(&1), None);
+        assert_eq!(cache.get(&0), Some("a"));
+        assert_eq!(cache.get(&2), Some("c"));
+
+        cache.insert(0, "d");
+        cache.insert(3, "e");
+        assert_eq!(cache.get(&0), Some("d"));
+        assert_eq!(cache.get(&2), None);
+
+        let mut empty = LruCache::new(0);
+        empty.insert(0, "a");
+        assert_eq!(empty.get(&0), None);
+    }
+}
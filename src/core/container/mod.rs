@@ -1,3 +1,4 @@
 mod index_object;
+pub(crate) mod lru;
 pub mod path_network;
 mod undirected;
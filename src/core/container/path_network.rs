@@ -1,4 +1,4 @@
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 
 use rstar::RTree;
 
@@ -20,10 +20,6 @@ impl NodeId {
     pub fn new(id: usize) -> Self {
         Self(id)
     }
-
-    fn as_num(&self) -> usize {
-        self.0
-    }
 }
 
 /// NodeIdGenerator is a simple struct that generates random ids.
@@ -115,14 +111,13 @@ where
     }
 
     /// Remove a node from the network.
-    /// This function can be never used, but it is kept for future use.
-    #[allow(dead_code)]
-    fn remove_node(&mut self, node_id: NodeId) -> Option<NodeId> {
-        let neighbors = if let Some(neighbors) = self.path_connection.neighbors_iter(node_id) {
-            neighbors.copied().collect::<Vec<_>>()
-        } else {
-            return None;
-        };
+    pub(crate) fn remove_node(&mut self, node_id: NodeId) -> Option<NodeId> {
+        // isolated nodes have no neighbors in the connection graph.
+        let neighbors = self
+            .path_connection
+            .neighbors_iter(node_id)
+            .map(|neighbors| neighbors.copied().collect::<Vec<_>>())
+            .unwrap_or_default();
 
         let site = if let Some(node) = self.nodes.get(&node_id) {
             (*node).into()
@@ -247,17 +242,106 @@ where
             .map(|object| object.node_ids())
     }
 
+    /// Iterate paths in order of the distance from the site, with the distance.
+    pub fn paths_nearest_iter(&self, site: Site) -> impl Iterator<Item = (&(NodeId, NodeId), f64)> {
+        self.path_tree
+            .nearest_neighbor_iter_with_distance_2(&[site.x, site.y])
+            .map(|(object, distance_2)| (object.node_ids(), distance_2.sqrt()))
+    }
+
+    /// Get faces (regions enclosed by paths) of the network.
+    ///
+    /// The network is assumed to be planar (paths never cross each other without nodes).
+    /// Each face is a list of nodes along its boundary in counterclockwise order in the x-y plane.
+    /// Outer faces of the connected components are excluded.
+    pub fn faces(&self) -> Vec<Vec<NodeId>> {
+        let site_of =
+            |node_id: &NodeId| -> Option<Site> { Some((*self.nodes.get(node_id)?).into()) };
+
+        // neighbors of each node sorted by the angle.
+        let sorted_neighbors = self
+            .nodes
+            .keys()
+            .filter_map(|node_id| {
+                let site = site_of(node_id)?;
+                let mut neighbors = self
+                    .path_connection
+                    .neighbors_iter(*node_id)?
+                    .filter_map(|neighbor| {
+                        let neighbor_site = site_of(neighbor)?;
+                        let angle = (neighbor_site.y - site.y).atan2(neighbor_site.x - site.x);
+                        Some((*neighbor, angle))
+                    })
+                    .collect::<Vec<_>>();
+                neighbors.sort_by(|a, b| a.1.total_cmp(&b.1));
+                Some((
+                    *node_id,
+                    neighbors
+                        .into_iter()
+                        .map(|(neighbor, _)| neighbor)
+                        .collect::<Vec<_>>(),
+                ))
+            })
+            .collect::<BTreeMap<_, _>>();
+
+        // trace the boundary of the face on the left side of each directed path.
+        let mut visited = BTreeSet::new();
+        let mut faces = Vec::new();
+        for (start, neighbors) in &sorted_neighbors {
+            for next in neighbors {
+                let (mut from, mut to) = (*start, *next);
+                let mut face = Vec::new();
+                while visited.insert((from, to)) {
+                    face.push(from);
+                    let around = if let Some(around) = sorted_neighbors.get(&to) {
+                        around
+                    } else {
+                        break;
+                    };
+                    let index = around
+                        .iter()
+                        .position(|neighbor| *neighbor == from)
+                        .unwrap_or_default();
+                    (from, to) = (to, around[(index + around.len() - 1) % around.len()]);
+                }
+                if face.len() < 3 {
+                    continue;
+                }
+                let signed_area = (0..face.len())
+                    .filter_map(|i| {
+                        let site = site_of(&face[i])?;
+                        let site_next = site_of(&face[(i + 1) % face.len()])?;
+                        Some(site.x * site_next.y - site_next.x * site.y)
+                    })
+                    .sum::<f64>();
+                if signed_area > 0.0 {
+                    faces.push(face);
+                }
+            }
+        }
+        faces
+    }
+
     /// Parse the network into a list of nodes and paths.
     ///
     /// This function is not exposed now, but it may be useful in the future.
+    ///
+    /// Nodes are listed in the order of `NodeId`, and paths refer to the indices of the list.
     fn parse(&self) -> (Vec<N>, Vec<(usize, usize)>) {
-        let nodes = self.nodes.iter().map(|(_, node)| *node).collect::<Vec<_>>();
+        let nodes = self.nodes.values().copied().collect::<Vec<_>>();
+        // node ids are not contiguous after nodes are removed.
+        let indices = self
+            .nodes
+            .keys()
+            .enumerate()
+            .map(|(index, node_id)| (*node_id, index))
+            .collect::<BTreeMap<_, _>>();
         let paths = self
             .path_tree
             .iter()
-            .map(|object| {
+            .filter_map(|object| {
                 let (start, end) = object.node_ids();
-                (start.as_num(), end.as_num())
+                Some((*indices.get(start)?, *indices.get(end)?))
             })
             .collect::<Vec<_>>();
         (nodes, paths)
@@ -335,8 +419,21 @@ where
 
     /// Get the optimized path network.
     pub fn reconstruct(self) -> Option<Self> {
+        self.reconstruct_with_id_map().map(|(network, _)| network)
+    }
+
+    /// Reconstruct the network, and return the map from the old node ids to the new ones.
+    pub fn reconstruct_with_id_map(self) -> Option<(Self, BTreeMap<NodeId, NodeId>)> {
         let (nodes, paths) = self.parse();
-        Self::from(nodes, &paths)
+        let network = Self::from(nodes, &paths)?;
+        // both of the old and new ids are ordered in the same way as the parsed nodes.
+        let id_map = self
+            .nodes
+            .keys()
+            .copied()
+            .zip(network.nodes.keys().copied())
+            .collect();
+        Some((network, id_map))
     }
 
     /// This function is only for testing
@@ -395,6 +492,58 @@ mod tests {
         assert!(network.check_path_state_is_consistent());
     }
 
+    #[test]
+    fn test_paths_nearest_iter() {
+        let mut network = PathNetwork::new();
+        let node0 = network.add_node(Site::new(0.0, 0.0));
+        let node1 = network.add_node(Site::new(4.0, 0.0));
+        let node2 = network.add_node(Site::new(0.0, 3.0));
+        let node3 = network.add_node(Site::new(4.0, 3.0));
+
+        network.add_path(node0, node1);
+        network.add_path(node2, node3);
+
+        // the nearest point of the path is its end.
+        let nearest = network
+            .paths_nearest_iter(Site::new(6.0, 2.0))
+            .map(|(_, distance)| distance)
+            .collect::<Vec<_>>();
+        assert_eq!(nearest.len(), 2);
+        assert!((nearest[0] - 5.0_f64.sqrt()).abs() < 1e-6);
+        assert!((nearest[1] - 8.0_f64.sqrt()).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_faces() {
+        // a square divided by a diagonal, with a dead end inside and a path outside.
+        let mut network = PathNetwork::new();
+        let node0 = network.add_node(Site::new(0.0, 0.0));
+        let node1 = network.add_node(Site::new(2.0, 0.0));
+        let node2 = network.add_node(Site::new(2.0, 2.0));
+        let node3 = network.add_node(Site::new(0.0, 2.0));
+        let node4 = network.add_node(Site::new(1.5, 0.5));
+        let node5 = network.add_node(Site::new(3.0, 3.0));
+
+        network.add_path(node0, node1);
+        network.add_path(node1, node2);
+        network.add_path(node2, node3);
+        network.add_path(node3, node0);
+        network.add_path(node0, node2);
+        network.add_path(node1, node4);
+        network.add_path(node2, node5);
+
+        let mut faces = network.faces();
+        assert_eq!(faces.len(), 2);
+
+        faces.sort_by_key(|face| face.len());
+        let mut upper = faces[0].clone();
+        upper.sort();
+        assert_eq!(upper, vec![node0, node2, node3]);
+        // the dead end is included in the boundary of the lower face.
+        assert_eq!(faces[1].len(), 5);
+        assert_eq!(faces[1].iter().filter(|node| **node == node1).count(), 2);
+    }
+
     #[test]
     fn test_path_crossing_no_crosses() {
         let mut network = PathNetwork::new();
@@ -417,7 +566,7 @@ mod tests {
     fn test_path_crossing_all_cross() {
         let mut network = PathNetwork::new();
 
-        let sites = vec![
+        let sites = [
             Site::new(0.0, 2.0),
             Site::new(2.0, 2.0),
             Site::new(2.0, 0.0),
@@ -583,7 +732,7 @@ mod tests {
             let mut paths = Vec::new();
             for i in 0..nodes.len() {
                 for j in i + 1..nodes.len() {
-                    if xorshift(i * nodes.len() + j) % 2 == 0 {
+                    if xorshift(i * nodes.len() + j).is_multiple_of(2) {
                         paths.push((i, j));
                     }
                 }
@@ -634,4 +783,34 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_reconstruction_after_removal() {
+        let mut network: PathNetwork<Site> = PathNetwork::new();
+        let node0 = network.add_node(Site::new(0.0, 0.0));
+        let node1 = network.add_node(Site::new(1.0, 0.0));
+        let node2 = network.add_node(Site::new(2.0, 0.0));
+        let node3 = network.add_node(Site::new(3.0, 0.0));
+        let isolated = network.add_node(Site::new(4.0, 0.0));
+        network.add_path(node0, node1);
+        network.add_path(node2, node3);
+
+        assert_eq!(network.remove_node(node1), Some(node1));
+        assert_eq!(network.remove_node(isolated), Some(isolated));
+        assert!(network.get_node(isolated).is_none());
+
+        let (reconstructed, id_map) = network.reconstruct_with_id_map().unwrap();
+        assert_eq!(id_map.len(), 3);
+        assert!(reconstructed.has_path(id_map[&node2], id_map[&node3]));
+        assert_eq!(
+            reconstructed.get_node(id_map[&node3]),
+            Some(&Site::new(3.0, 0.0))
+        );
+        assert_eq!(
+            reconstructed
+                .neighbors_iter(id_map[&node0])
+                .map_or(0, |neighbors| neighbors.count()),
+            0
+        );
+    }
 }
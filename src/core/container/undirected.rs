@@ -35,7 +35,7 @@ where
 
     /// Check if there is an edge between two nodes.
     pub fn has_edge(&self, a: N, b: N) -> bool {
-        self.edges.get(&a).map_or(false, |set| set.contains(&b))
+        self.edges.get(&a).is_some_and(|set| set.contains(&b))
     }
 
     /// Remove an edge from the graph.
@@ -92,33 +92,33 @@ mod tests {
         let neighbors = graph
             .neighbors_iter(103)
             .unwrap()
-            .map(|&x| x)
+            .copied()
             .collect::<Vec<_>>();
         assert_eq!(neighbors, vec![25, 85]);
 
         assert_eq!(graph.order(), 5);
         assert_eq!(graph.size(), 4);
-        assert_eq!(graph.has_edge(103, 25), true);
-        assert_eq!(graph.has_edge(25, 103), true);
-        assert_eq!(graph.has_edge(85, 103), true);
-        assert_eq!(graph.has_edge(103, 85), true);
-        assert_eq!(graph.has_edge(85, 25), false);
-        assert_eq!(graph.has_edge(25, 85), false);
+        assert!(graph.has_edge(103, 25));
+        assert!(graph.has_edge(25, 103));
+        assert!(graph.has_edge(85, 103));
+        assert!(graph.has_edge(103, 85));
+        assert!(!graph.has_edge(85, 25));
+        assert!(!graph.has_edge(25, 85));
 
         graph.remove_edge(103, 25);
 
         assert_eq!(graph.order(), 5);
         assert_eq!(graph.size(), 3);
-        assert_eq!(graph.has_edge(103, 25), false);
-        assert_eq!(graph.has_edge(25, 103), false);
-        assert_eq!(graph.has_edge(85, 103), true);
+        assert!(!graph.has_edge(103, 25));
+        assert!(!graph.has_edge(25, 103));
+        assert!(graph.has_edge(85, 103));
 
         graph.remove_edge(85, 103);
 
         assert_eq!(graph.order(), 4);
         assert_eq!(graph.size(), 2);
-        assert_eq!(graph.has_edge(85, 103), false);
-        assert_eq!(graph.has_edge(103, 85), false);
-        assert_eq!(graph.has_edge(85, 32), true);
+        assert!(!graph.has_edge(85, 103));
+        assert!(!graph.has_edge(103, 85));
+        assert!(graph.has_edge(85, 32));
     }
 }
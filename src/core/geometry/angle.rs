@@ -78,6 +78,12 @@ impl Angle {
         }
     }
 
+    /// Calculate the smaller angle difference in the range of [0, PI].
+    pub fn diff(&self, other: &Self) -> f64 {
+        self.diff_clockwise_to(other)
+            .min(self.diff_counterclockwise_to(other))
+    }
+
     /// Create an iterator of angles between two angles.
     fn iter_range_closer(&self, other: &Self, step_num: usize) -> AngleIter {
         let (rad_from, rad_to) = {
@@ -156,6 +162,22 @@ mod tests {
         assert_eq!(Angle::new(-2.0 * std::f64::consts::PI).0, 0.0);
     }
 
+    #[test]
+    fn test_angle_diff() {
+        assert_eq!(
+            Angle::new(0.0).diff(&Angle::new(std::f64::consts::PI)),
+            std::f64::consts::PI
+        );
+        assert_eq!(
+            Angle::new(0.5 * std::f64::consts::PI).diff(&Angle::new(0.0)),
+            0.5 * std::f64::consts::PI
+        );
+        assert_eq!(
+            Angle::new(-0.5 * std::f64::consts::PI).diff(&Angle::new(0.0)),
+            0.5 * std::f64::consts::PI
+        );
+    }
+
     #[test]
     fn test_angle_diff_clockwise_to() {
         assert_eq!(
@@ -1,3 +1,4 @@
 pub mod angle;
 pub mod line_segment;
+pub mod polygon;
 pub mod site;
@@ -0,0 +1,129 @@
+use super::{line_segment::LineSegment, site::Site};
+
+/// Representation of a simple polygon.
+///
+/// The polygon is implicitly closed, so the last vertex is connected to the first one.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Polygon {
+    vertices: Vec<Site>,
+}
+
+impl Polygon {
+    /// Create a polygon from vertices.
+    pub fn new(vertices: Vec<Site>) -> Self {
+        Self { vertices }
+    }
+
+    /// Get the vertices of the polygon.
+    pub fn vertices(&self) -> &[Site] {
+        &self.vertices
+    }
+
+    /// Get the edges of the polygon as an iterator.
+    pub fn edges_iter(&self) -> impl Iterator<Item = LineSegment> + '_ {
+        let len = self.vertices.len();
+        (0..len).map(move |i| LineSegment::new(self.vertices[i], self.vertices[(i + 1) % len]))
+    }
+
+    /// Check if the site is inside the polygon.
+    pub fn contains(&self, site: &Site) -> bool {
+        // ray casting to the positive x direction
+        self.edges_iter()
+            .filter(|edge| {
+                let (a, b) = (edge.0, edge.1);
+                if (a.y > site.y) == (b.y > site.y) {
+                    return false;
+                }
+                let x = a.x + (site.y - a.y) / (b.y - a.y) * (b.x - a.x);
+                site.x < x
+            })
+            .count()
+            % 2
+            == 1
+    }
+
+    /// Calculate the intersections of the line segment and the edges of the polygon.
+    ///
+    /// The intersections are sorted by the distance from the start of the line segment.
+    pub fn get_intersections(&self, line: &LineSegment) -> Vec<Site> {
+        let mut intersections = self
+            .edges_iter()
+            .filter_map(|edge| edge.get_intersection(line))
+            .collect::<Vec<_>>();
+        intersections.sort_by(|a, b| a.distance_2(&line.0).total_cmp(&b.distance_2(&line.0)));
+        intersections
+    }
+
+    /// Calculate the area of the polygon.
+    pub fn area(&self) -> f64 {
+        self.edges_iter()
+            .map(|edge| edge.0.x * edge.1.y - edge.1.x * edge.0.y)
+            .sum::<f64>()
+            .abs()
+            / 2.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn square() -> Polygon {
+        Polygon::new(vec![
+            Site::new(0.0, 0.0),
+            Site::new(2.0, 0.0),
+            Site::new(2.0, 2.0),
+            Site::new(0.0, 2.0),
+        ])
+    }
+
+    #[test]
+    fn test_contains() {
+        let polygon = square();
+        assert!(polygon.contains(&Site::new(1.0, 1.0)));
+        assert!(polygon.contains(&Site::new(0.1, 1.9)));
+        assert!(!polygon.contains(&Site::new(3.0, 1.0)));
+        assert!(!polygon.contains(&Site::new(-1.0, 1.0)));
+        assert!(!polygon.contains(&Site::new(1.0, -0.5)));
+
+        // concave polygon
+        let polygon = Polygon::new(vec![
+            Site::new(0.0, 0.0),
+            Site::new(3.0, 0.0),
+            Site::new(3.0, 3.0),
+            Site::new(2.0, 3.0),
+            Site::new(2.0, 1.0),
+            Site::new(1.0, 1.0),
+            Site::new(1.0, 3.0),
+            Site::new(0.0, 3.0),
+        ]);
+        assert!(polygon.contains(&Site::new(0.5, 2.0)));
+        assert!(polygon.contains(&Site::new(2.5, 2.0)));
+        assert!(!polygon.contains(&Site::new(1.5, 2.0)));
+    }
+
+    #[test]
+    fn test_get_intersections() {
+        let polygon = square();
+        let line = LineSegment::new(Site::new(-1.0, 1.0), Site::new(3.0, 1.0));
+        let intersections = polygon.get_intersections(&line);
+        assert_eq!(
+            intersections,
+            vec![Site::new(0.0, 1.0), Site::new(2.0, 1.0)]
+        );
+
+        let line = LineSegment::new(Site::new(0.5, 0.5), Site::new(1.5, 1.5));
+        assert!(polygon.get_intersections(&line).is_empty());
+    }
+
+    #[test]
+    fn test_area() {
+        assert_eq!(square().area(), 4.0);
+        let triangle = Polygon::new(vec![
+            Site::new(0.0, 0.0),
+            Site::new(0.0, 3.0),
+            Site::new(4.0, 0.0),
+        ]);
+        assert_eq!(triangle.area(), 6.0);
+    }
+}
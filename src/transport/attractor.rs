@@ -0,0 +1,84 @@
+use crate::core::geometry::{angle::Angle, line_segment::LineSegment, site::Site};
+
+/// Point of interest (port, mine, neighboring town, etc.) which attracts the growth of paths.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Attractor {
+    /// The site of the attractor.
+    pub site: Site,
+    /// The radius of influence.
+    pub radius: f64,
+    /// The strength of the influence.
+    ///
+    /// This value is added to the priority of the candidate paths heading to the attractor,
+    /// so it should be the same scale as the value from `PathPrioritizator`.
+    pub strength: f64,
+}
+
+impl Attractor {
+    /// Create a new attractor.
+    pub fn new(site: Site, radius: f64, strength: f64) -> Self {
+        Self {
+            site,
+            radius,
+            strength,
+        }
+    }
+
+    /// Calculate the bias for the path heading to `angle` from `site_start`.
+    ///
+    /// The bias is maximum when the path heads straight to the attractor,
+    /// and decreases linearly with the distance from the attractor.
+    pub fn bias(&self, site_start: &Site, angle: Angle) -> f64 {
+        let distance = site_start.distance(&self.site);
+        if distance >= self.radius || distance == 0.0 {
+            return 0.0;
+        }
+        let angle_to = site_start.get_angle(&self.site);
+        let cos = angle.unit_x() * angle_to.unit_x() + angle.unit_y() * angle_to.unit_y();
+        self.strength * (1.0 - distance / self.radius) * cos
+    }
+
+    /// Check if the path should be snapped to the attractor.
+    pub fn snaps(&self, line: &LineSegment, snap_distance: f64) -> bool {
+        line.0 != self.site && line.get_distance(&self.site) < snap_distance
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bias() {
+        let attractor = Attractor::new(Site::new(0.0, -10.0), 20.0, 1.0);
+        let site = Site::new(0.0, 0.0);
+
+        // heading straight to the attractor
+        assert!((attractor.bias(&site, Angle::new(0.0)) - 0.5).abs() < 1e-6);
+        // heading perpendicular
+        assert!(
+            attractor
+                .bias(&site, Angle::new(0.5 * std::f64::consts::PI))
+                .abs()
+                < 1e-6
+        );
+        // heading away
+        assert!((attractor.bias(&site, Angle::new(std::f64::consts::PI)) + 0.5).abs() < 1e-6);
+        // out of the influence
+        assert_eq!(attractor.bias(&Site::new(0.0, 15.0), Angle::new(0.0)), 0.0);
+        // at the attractor
+        assert_eq!(attractor.bias(&attractor.site, Angle::new(0.0)), 0.0);
+    }
+
+    #[test]
+    fn test_snaps() {
+        let attractor = Attractor::new(Site::new(1.0, 0.2), 20.0, 1.0);
+        let line = LineSegment::new(Site::new(0.0, 0.0), Site::new(2.0, 0.0));
+        assert!(attractor.snaps(&line, 0.25));
+        assert!(!attractor.snaps(&line, 0.15));
+
+        // the path starting from the attractor is not snapped.
+        let line = LineSegment::new(attractor.site, Site::new(2.0, 0.0));
+        assert!(!attractor.snaps(&line, 0.25));
+    }
+}
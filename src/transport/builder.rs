@@ -1,38 +1,82 @@
-use std::collections::BinaryHeap;
+use std::collections::{BTreeMap, BTreeSet, BinaryHeap};
 
 use crate::core::{
     container::path_network::{NodeId, PathNetwork},
-    geometry::{angle::Angle, line_segment::LineSegment, site::Site},
+    geometry::{angle::Angle, line_segment::LineSegment, polygon::Polygon, site::Site},
 };
 
 use super::{
+    attractor::Attractor,
+    constraints::{GrowthConstraints, KeepOutZone},
+    context::GrowthContext,
+    density::RoadDensity,
     growth::{
         growth_type::{BridgeNodeType, GrowthTypes, NextNodeType},
-        stump::Stump,
+        stump::{JunctionDistance, Stump, StumpFailure},
+        switchback::Switchback,
     },
     node::TransportNode,
-    params::{metrics::PathMetrics, numeric::Stage},
-    traits::{PathPrioritizator, RandomF64Provider, TerrainProvider, TransportRulesProvider},
+    params::{
+        metrics::{GrowthMetrics, PathExtension, PathMetrics},
+        numeric::Stage,
+        rules::TransportRules,
+    },
+    payload::NodePayloads,
+    planner::HighwayPlanner,
+    postprocess::{
+        dead_end::{self, DeadEndRules},
+        roundabout::{self, RoundaboutRules},
+    },
+    random::PathRandom,
+    traits::{
+        BlockAreaProvider, PathPrioritizator, RandomF64Provider, TerrainProvider,
+        TransportRulesProvider,
+    },
 };
 
-pub struct TransportBuilder<'a, RP, TP, PP>
+/// Bounding box `(min, max)` of a region.
+type Bounds = (Site, Site);
+
+/// Builder of transport networks.
+///
+/// `M` is the type of metrics accumulated along the growth of paths,
+/// which are passed to the providers via `GrowthContext`.
+/// `P` is the type of payloads attached to the nodes (see `with_node_payload`).
+pub struct TransportBuilder<'a, RP, TP, PP, M = PathMetrics, P = ()>
 where
-    RP: TransportRulesProvider,
+    RP: TransportRulesProvider<M>,
     TP: TerrainProvider,
-    PP: PathPrioritizator,
+    PP: PathPrioritizator<M>,
+    M: GrowthMetrics,
 {
     path_network: PathNetwork<TransportNode>,
     rules_provider: &'a RP,
     terrain_provider: &'a TP,
     path_prioritizator: &'a PP,
-    stump_heap: BinaryHeap<Stump>,
+    constraints: GrowthConstraints,
+    /// Attractors which are not reached by any node yet.
+    attractors: Vec<Attractor>,
+    /// Attractors which are reached by nodes, which don't attract the growth anymore.
+    reached_attractors: Vec<Attractor>,
+    road_density: Option<RoadDensity>,
+    /// The origin node which each node descends from.
+    node_origins: BTreeMap<NodeId, NodeId>,
+    stump_heap: BinaryHeap<Stump<M>>,
+    /// The number of stumps pushed so far, which orders the stumps with the same priority.
+    stump_sequence: u64,
+    /// The seed of the random values derived from each path (see `seed_per_path`).
+    path_seed: Option<u64>,
+    node_payloads: Option<NodePayloads<'a, P>>,
+    /// Bounding boxes of the modifications of the network, recorded while a batch of stumps is applied.
+    modified_bounds: Option<Vec<Bounds>>,
 }
 
-impl<'a, RP, TP, PP> TransportBuilder<'a, RP, TP, PP>
+impl<'a, RP, TP, PP, M> TransportBuilder<'a, RP, TP, PP, M>
 where
-    RP: TransportRulesProvider,
+    RP: TransportRulesProvider<M>,
     TP: TerrainProvider,
-    PP: PathPrioritizator,
+    PP: PathPrioritizator<M>,
+    M: GrowthMetrics,
 {
     /// Create a new `TransportBuilder`.
     pub fn new(
@@ -45,33 +89,474 @@ where
             rules_provider,
             terrain_provider,
             path_prioritizator,
+            constraints: GrowthConstraints::default(),
+            attractors: Vec::new(),
+            reached_attractors: Vec::new(),
+            road_density: None,
+            node_origins: BTreeMap::new(),
             stump_heap: BinaryHeap::new(),
+            stump_sequence: 0,
+            path_seed: None,
+            node_payloads: None,
+            modified_bounds: None,
+        }
+    }
+}
+
+impl<'a, RP, TP, PP, M, P> TransportBuilder<'a, RP, TP, PP, M, P>
+where
+    RP: TransportRulesProvider<M>,
+    TP: TerrainProvider,
+    PP: PathPrioritizator<M>,
+    M: GrowthMetrics,
+{
+    /// Attach payloads to the nodes, which are created by `create` when nodes are added.
+    ///
+    /// `create` receives the added node and the payload of the node which it's grown from.
+    /// Nodes which are not grown from other nodes (origins, nodes on the routes of highways,
+    /// and nodes added by post-processes) don't have the parent payload. Payloads are also created for the existing nodes.
+    pub fn with_node_payload<Q, F>(self, create: F) -> TransportBuilder<'a, RP, TP, PP, M, Q>
+    where
+        F: Fn(&TransportNode, Option<&Q>) -> Q + 'a,
+    {
+        let node_payloads = NodePayloads::from_network(&self.path_network, Box::new(create));
+        TransportBuilder {
+            path_network: self.path_network,
+            rules_provider: self.rules_provider,
+            terrain_provider: self.terrain_provider,
+            path_prioritizator: self.path_prioritizator,
+            constraints: self.constraints,
+            attractors: self.attractors,
+            reached_attractors: self.reached_attractors,
+            road_density: self.road_density,
+            node_origins: self.node_origins,
+            stump_heap: self.stump_heap,
+            stump_sequence: self.stump_sequence,
+            path_seed: self.path_seed,
+            node_payloads: Some(node_payloads),
+            modified_bounds: self.modified_bounds,
+        }
+    }
+
+    /// Get the payload of the node.
+    pub fn get_node_payload(&self, node_id: NodeId) -> Option<&P> {
+        self.node_payloads.as_ref()?.get(node_id)
+    }
+
+    /// Set the boundary polygon which bounds the growth of the network.
+    ///
+    /// Paths leaving the boundary are clipped at the boundary.
+    /// This should be called before adding origins.
+    pub fn set_boundary(mut self, boundary: Polygon) -> Self {
+        self.constraints = self.constraints.boundary(boundary);
+        self
+    }
+
+    /// Add a zone where paths are not allowed to be constructed.
+    ///
+    /// This should be called before adding origins.
+    pub fn add_keep_out_zone(mut self, zone: KeepOutZone) -> Self {
+        self.constraints = self.constraints.keep_out_zone(zone);
+        self
+    }
+
+    /// Add an attractor which the paths grow toward.
+    ///
+    /// Stumps within the radius of the attractor bias their direction toward it,
+    /// and paths passing close to the attractor are snapped to a node at the attractor.
+    pub fn add_attractor(mut self, attractor: Attractor) -> Self {
+        if self.has_node_at(attractor.site) {
+            self.reached_attractors.push(attractor);
+        } else {
+            self.attractors.push(attractor);
+        }
+        self
+    }
+
+    /// Track the density of paths (length of paths per unit area) on a grid with the cell size.
+    ///
+    /// The density is passed to `TransportRulesProvider` and `PathPrioritizator` via the context
+    /// to saturate the growth in dense areas.
+    pub fn track_road_density(mut self, cell_size: f64) -> Self {
+        self.road_density = Some(RoadDensity::from_network(&self.path_network, cell_size));
+        self
+    }
+
+    /// Record the modification of the network between the sites.
+    fn record_modification(&mut self, a: Site, b: Site) {
+        if let Some(modified_bounds) = self.modified_bounds.as_mut() {
+            modified_bounds.push((
+                Site::new(a.x.min(b.x), a.y.min(b.y)),
+                Site::new(a.x.max(b.x), a.y.max(b.y)),
+            ));
         }
     }
 
+    /// Check if the network is modified within the bounding box since the recording started.
+    #[cfg(feature = "rayon")]
+    fn is_modified_within(&self, (min, max): Bounds) -> bool {
+        self.modified_bounds
+            .as_ref()
+            .is_some_and(|modified_bounds| {
+                modified_bounds.iter().any(|(modified_min, modified_max)| {
+                    modified_min.x <= max.x
+                        && min.x <= modified_max.x
+                        && modified_min.y <= max.y
+                        && min.y <= modified_max.y
+                })
+            })
+    }
+
+    /// Derive the random values of each growth from the seed and the sites of the path,
+    /// instead of drawing them from the random provider passed to the iterations.
+    ///
+    /// The growth from a path doesn't depend on the order in which the paths are created,
+    /// so local edits (e.g. adding an origin) only change the network locally.
+    pub fn seed_per_path(mut self, seed: u64) -> Self {
+        self.path_seed = Some(seed);
+        self
+    }
+
+    /// Add a path to the network and the road density.
+    fn add_path(&mut self, start: NodeId, end: NodeId) {
+        if self.path_network.add_path(start, end).is_none() {
+            return;
+        }
+        if let (Some(node_start), Some(node_end)) = (
+            self.path_network.get_node(start).copied(),
+            self.path_network.get_node(end).copied(),
+        ) {
+            self.record_modification(node_start.site, node_end.site);
+        }
+        if let (Some(density), Some(node_start), Some(node_end)) = (
+            self.road_density.as_mut(),
+            self.path_network.get_node(start),
+            self.path_network.get_node(end),
+        ) {
+            density.add_path(node_start.site, node_end.site);
+        }
+    }
+
+    /// Remove a path from the network and the road density.
+    fn remove_path(&mut self, start: NodeId, end: NodeId) {
+        if !self.path_network.has_path(start, end) {
+            return;
+        }
+        if let (Some(node_start), Some(node_end)) = (
+            self.path_network.get_node(start).copied(),
+            self.path_network.get_node(end).copied(),
+        ) {
+            self.record_modification(node_start.site, node_end.site);
+        }
+        if let (Some(density), Some(node_start), Some(node_end)) = (
+            self.road_density.as_mut(),
+            self.path_network.get_node(start),
+            self.path_network.get_node(end),
+        ) {
+            density.remove_path(node_start.site, node_end.site);
+        }
+        self.path_network.remove_path(start, end);
+    }
+
+    /// Rebuild the road density, the node payloads and the reached attractors
+    /// after the network is modified directly.
+    fn sync_with_network(&mut self) {
+        let attractors = std::mem::take(&mut self.attractors)
+            .into_iter()
+            .chain(std::mem::take(&mut self.reached_attractors))
+            .collect::<Vec<_>>();
+        (self.reached_attractors, self.attractors) = attractors
+            .into_iter()
+            .partition(|attractor| self.has_node_at(attractor.site));
+        if let Some(density) = self.road_density.as_mut() {
+            *density = RoadDensity::from_network(&self.path_network, density.cell_size());
+        }
+        if let Some(node_payloads) = self.node_payloads.as_mut() {
+            node_payloads.sync(&self.path_network);
+        }
+    }
+
+    /// Create the context of the path extended from the node.
+    fn growth_context<'b>(
+        &'b self,
+        node_id: NodeId,
+        heading: Angle,
+        stage: Stage,
+        metrics: &'b M,
+    ) -> Option<GrowthContext<'b, M>> {
+        let node = self.path_network.get_node(node_id)?;
+        let degree = self
+            .path_network
+            .neighbors_iter(node_id)
+            .map_or(0, |neighbors| neighbors.count());
+        let nearest_path_distance = self
+            .path_network
+            .paths_nearest_iter(node.site)
+            .find(|((start, end), _)| *start != node_id && *end != node_id)
+            .map(|(_, distance)| distance);
+        Some(GrowthContext {
+            node_id,
+            node,
+            heading,
+            stage,
+            metrics,
+            degree,
+            nearest_path_distance,
+            origin: self.node_origins.get(&node_id).copied(),
+            road_density: self
+                .road_density
+                .as_ref()
+                .map(|density| density.get_density(&node.site)),
+            network: &self.path_network,
+        })
+    }
+
+    /// Add a node to the network and create its payload.
+    fn add_node(&mut self, node: TransportNode, parent_id: Option<NodeId>) -> NodeId {
+        let node_id = self.path_network.add_node(node);
+        self.record_modification(node.site, node.site);
+        if self
+            .attractors
+            .iter()
+            .any(|attractor| attractor.site == node.site)
+        {
+            let (reached, active) = std::mem::take(&mut self.attractors)
+                .into_iter()
+                .partition::<Vec<_>, _>(|attractor| attractor.site == node.site);
+            self.attractors = active;
+            self.reached_attractors.extend(reached);
+        }
+        if let Some(node_payloads) = self.node_payloads.as_mut() {
+            node_payloads.on_add(node_id, &node, parent_id);
+        }
+        node_id
+    }
+
+    /// Add a node created by the growth from the parent node.
+    ///
+    /// The node descends from the same origin as the parent node.
+    fn add_node_from(&mut self, node: TransportNode, parent_id: NodeId) -> NodeId {
+        let node_id = self.add_node(node, Some(parent_id));
+        if let Some(origin) = self.node_origins.get(&parent_id).copied() {
+            self.node_origins.insert(node_id, origin);
+        }
+        node_id
+    }
+
+    /// Remove a node from the network with its payload.
+    fn remove_node(&mut self, node_id: NodeId) {
+        let node = self.path_network.get_node(node_id).copied();
+        self.path_network.remove_node(node_id);
+        if let Some(node) = node {
+            self.record_modification(node.site, node.site);
+            // the attractors at the site attract the growth again.
+            if !self.has_node_at(node.site) {
+                let (reached, active) = std::mem::take(&mut self.reached_attractors)
+                    .into_iter()
+                    .partition::<Vec<_>, _>(|attractor| attractor.site != node.site);
+                self.reached_attractors = reached;
+                self.attractors.extend(active);
+            }
+        }
+        self.node_origins.remove(&node_id);
+        if let Some(node_payloads) = self.node_payloads.as_mut() {
+            node_payloads.on_remove(node_id);
+        }
+    }
+
+    /// Check if there is a node exactly on the site.
+    fn has_node_at(&self, site: Site) -> bool {
+        self.path_network
+            .nodes_around_site_iter(site, 0.0)
+            .next()
+            .is_some()
+    }
+
+    /// Get paths around the node which may run in parallel with the path from the node.
+    ///
+    /// Paths connected to the node within the clearance along the network (e.g. the path being extended)
+    /// are excluded. If the clearance is not required, no path is returned.
+    fn nearby_paths(&self, node_id: NodeId, rules: &TransportRules) -> Vec<LineSegment> {
+        let clearance = rules.clearance_rules.min_parallel_clearance;
+        if clearance <= 0.0 {
+            return vec![];
+        }
+        let site = if let Some(node) = self.path_network.get_node(node_id) {
+            node.site
+        } else {
+            return vec![];
+        };
+        let connected = Self::walked_distances(&self.path_network, node_id, clearance);
+        let reach = rules.path_normal_length + rules.bridge_rules.max_bridge_length + clearance;
+        self.path_network
+            .paths_touching_rect_iter(
+                Site::new(site.x - reach, site.y - reach),
+                Site::new(site.x + reach, site.y + reach),
+            )
+            .filter(|(start, end)| !connected.contains_key(start) && !connected.contains_key(end))
+            .filter_map(|(start, end)| {
+                Some(LineSegment::new(
+                    self.path_network.get_node(*start)?.site,
+                    self.path_network.get_node(*end)?.site,
+                ))
+            })
+            .collect()
+    }
+
+    /// Get the nodes reachable from the node along paths shorter than the distance in total,
+    /// with the walked distances.
+    fn walked_distances(
+        path_network: &PathNetwork<TransportNode>,
+        node_id: NodeId,
+        distance: f64,
+    ) -> BTreeMap<NodeId, f64> {
+        let mut walked = BTreeMap::from([(node_id, 0.0)]);
+        let mut queue = vec![node_id];
+        while let Some(current_id) = queue.pop() {
+            let (current, current_walked) = if let Some(current) = path_network.get_node(current_id)
+            {
+                (current, walked[&current_id])
+            } else {
+                continue;
+            };
+            for (neighbor_id, neighbor) in path_network
+                .neighbors_iter(current_id)
+                .into_iter()
+                .flatten()
+            {
+                let neighbor_walked = current_walked + current.site.distance(&neighbor.site);
+                if neighbor_walked < distance
+                    && walked
+                        .get(&neighbor_id)
+                        .is_none_or(|walked| neighbor_walked < *walked)
+                {
+                    walked.insert(neighbor_id, neighbor_walked);
+                    queue.push(neighbor_id);
+                }
+            }
+        }
+        walked
+    }
+
+    /// Get the junctions around the node along the network within the distance.
+    ///
+    /// If no other junction is found, the distance to it is infinite.
+    fn junction_distance(
+        path_network: &PathNetwork<TransportNode>,
+        node_id: NodeId,
+        distance: f64,
+    ) -> Option<JunctionDistance> {
+        let degree = path_network.neighbors_iter(node_id)?.count();
+        let nearest_other = Self::walked_distances(path_network, node_id, distance)
+            .into_iter()
+            .filter(|(walked_id, _)| {
+                *walked_id != node_id
+                    && path_network
+                        .neighbors_iter(*walked_id)
+                        .is_some_and(|neighbors| neighbors.count() > 2)
+            })
+            .map(|(_, walked)| walked)
+            .fold(f64::INFINITY, f64::min);
+        Some(JunctionDistance {
+            degree,
+            nearest_other,
+        })
+    }
+
+    /// Push a stump to the heap.
+    fn push_stump(&mut self, stump: Stump<M>) {
+        self.stump_heap
+            .push(stump.with_sequence(self.stump_sequence));
+        self.stump_sequence += 1;
+    }
+
     /// Add a path stump to the path network.
     fn push_new_stump(
         &mut self,
         node_start_id: NodeId,
         angle_expected_end: Angle,
         stage: Stage,
-        metrics: PathMetrics,
+        metrics: M,
     ) -> Option<()> {
-        let node = self.path_network.get_node(node_start_id)?;
+        let context = self.growth_context(node_start_id, angle_expected_end, stage, &metrics)?;
 
-        let rules = self.rules_provider.get_rules(&node.site, stage, &metrics)?;
+        let rules = self.rules_provider.get_rules(&context)?;
 
         let stump = Stump::create(
             self.terrain_provider,
             self.path_prioritizator,
-            (node, node_start_id),
-            angle_expected_end,
-            stage,
+            &self.constraints,
+            &self.attractors,
+            &self.nearby_paths(node_start_id, &rules),
+            self.road_density.as_ref(),
+            &context,
             &rules,
-            &metrics,
-        )?;
+        )
+        .or_else(|failure| match failure {
+            // if the path is blocked by the slope, try to start a switchback sequence.
+            StumpFailure::TooSteep => Stump::create_switchback(
+                self.terrain_provider,
+                self.path_prioritizator,
+                &self.constraints,
+                self.road_density.as_ref(),
+                &context,
+                &Switchback::new(angle_expected_end),
+                &rules,
+            )
+            .ok_or(failure),
+            StumpFailure::Blocked => Err(failure),
+        })
+        .ok()?;
 
-        self.stump_heap.push(stump);
+        self.push_stump(stump);
+
+        Some(())
+    }
+
+    /// Add a path stump which continues the switchback sequence.
+    ///
+    /// If the original heading of the sequence is not blocked anymore, the sequence ends.
+    /// The sequence also ends if the path is blocked by other than the slope.
+    fn push_switchback_stump(
+        &mut self,
+        node_start_id: NodeId,
+        switchback: &Switchback,
+        leg_extension: f64,
+        stage: Stage,
+        metrics: M,
+    ) -> Option<()> {
+        let context = self.growth_context(node_start_id, switchback.heading(), stage, &metrics)?;
+
+        let rules = self.rules_provider.get_rules(&context)?;
+
+        let stump = Stump::create(
+            self.terrain_provider,
+            self.path_prioritizator,
+            &self.constraints,
+            &self.attractors,
+            &self.nearby_paths(node_start_id, &rules),
+            self.road_density.as_ref(),
+            &context,
+            &rules,
+        )
+        .or_else(|failure| match failure {
+            StumpFailure::TooSteep => Stump::create_switchback(
+                self.terrain_provider,
+                self.path_prioritizator,
+                &self.constraints,
+                self.road_density.as_ref(),
+                &context,
+                &switchback
+                    .extended(leg_extension, &rules.switchback_rules)
+                    .ok_or(failure)?,
+                &rules,
+            )
+            .ok_or(failure),
+            StumpFailure::Blocked => Err(failure),
+        })
+        .ok()?;
+
+        self.push_stump(stump);
 
         Some(())
     }
@@ -85,34 +570,183 @@ where
         angle_radian: f64,
         stage: Option<Stage>,
     ) -> Option<Self> {
+        self.insert_origin(origin_site, Angle::new(angle_radian), stage, true)?;
+        Some(self)
+    }
+
+    /// Add an origin node from which the path is extended only by `angle_radian`.
+    ///
+    /// This continues a path coming from outside of the boundary (e.g. from the neighbor tile).
+    pub fn add_directed_origin(
+        mut self,
+        origin_site: Site,
+        angle_radian: f64,
+        stage: Option<Stage>,
+    ) -> Option<Self> {
+        self.insert_origin(origin_site, Angle::new(angle_radian), stage, false)?;
+        Some(self)
+    }
+
+    /// Add an origin node extending the path by the angle (and the opposite path if `both_directions`).
+    ///
+    /// If the site is not allowed by the constraints or has no elevation, `None` is returned
+    /// and the builder is not modified. Otherwise the origin node is added
+    /// even if no path can be extended from it.
+    pub(crate) fn insert_origin(
+        &mut self,
+        origin_site: Site,
+        angle: Angle,
+        stage: Option<Stage>,
+        both_directions: bool,
+    ) -> Option<NodeId> {
         let stage = if let Some(stage) = stage {
             stage
         } else {
             Stage::from_num(0)
         };
+        if !self.constraints.allows_site(&origin_site) {
+            return None;
+        }
         let origin_node = TransportNode::new(
             origin_site,
             self.terrain_provider.get_elevation(&origin_site)?,
             stage,
             false,
         );
-        let origin_node_id = self.path_network.add_node(origin_node);
-        let origin_metrics = PathMetrics::default();
+        let origin_node_id = self.find_or_add_node(origin_node);
+        self.node_origins
+            .entry(origin_node_id)
+            .or_insert(origin_node_id);
 
-        self.push_new_stump(
-            origin_node_id,
-            Angle::new(angle_radian),
-            stage,
-            origin_metrics.incremented(false, false),
-        );
-        self.push_new_stump(
-            origin_node_id,
-            Angle::new(angle_radian).opposite(),
-            stage,
-            origin_metrics.incremented(false, false),
-        );
+        self.push_new_stump(origin_node_id, angle, stage, M::initial());
+        if both_directions {
+            self.push_new_stump(origin_node_id, angle.opposite(), stage, M::initial());
+        }
 
-        Some(self)
+        Some(origin_node_id)
+    }
+
+    /// Add highways between towns to the path network.
+    ///
+    /// The towns are linked by the backbone graph, and each link is routed by least-cost search over the terrain.
+    /// Highways are inserted as stage-0 paths, so this should be called before the growth starts.
+    /// To grow local paths from the towns, add origins at the sites of the towns.
+    pub fn add_highways(mut self, towns: &[Site], planner: &HighwayPlanner) -> Self {
+        let routes = planner.plan(self.terrain_provider, &self.constraints, towns);
+        let stage = Stage::from_num(0);
+
+        for route in routes {
+            let mut node_ids = Vec::with_capacity(route.len());
+            for route_site in route.iter() {
+                let node = TransportNode::new(route_site.site, route_site.elevation, stage, false);
+                node_ids.push(self.find_or_add_node(node));
+            }
+
+            for i in 1..route.len() {
+                let (node_start_id, node_end_id) = (node_ids[i - 1], node_ids[i]);
+                if route[i].is_bridge {
+                    let (start, end) = (route[i - 1], route[i]);
+                    let bridge_node_id = self.add_node(
+                        TransportNode::new(
+                            start.site.midpoint(&end.site),
+                            (start.elevation + end.elevation) / 2.0,
+                            stage,
+                            true,
+                        ),
+                        Some(node_start_id),
+                    );
+                    self.add_path(node_start_id, bridge_node_id);
+                    self.add_path(bridge_node_id, node_end_id);
+                } else {
+                    self.add_path_with_intersections(node_start_id, node_end_id);
+                }
+            }
+        }
+
+        self
+    }
+
+    /// Find the node placed exactly on the site of `node`, or add `node` to the path network.
+    fn find_or_add_node(&mut self, node: TransportNode) -> NodeId {
+        let existing = self
+            .path_network
+            .nodes_around_site_iter(node.site, 0.0)
+            .next()
+            .copied();
+        if let Some(node_id) = existing {
+            node_id
+        } else {
+            self.add_node(node, None)
+        }
+    }
+
+    /// Add a path with creating intersections on the crossing paths.
+    ///
+    /// Bridges are crossed without intersections.
+    fn add_path_with_intersections(&mut self, node_start_id: NodeId, node_end_id: NodeId) {
+        let mut node_start_id = node_start_id;
+        loop {
+            let (node_start, node_end) = if let (Some(start), Some(end)) = (
+                self.path_network.get_node(node_start_id),
+                self.path_network.get_node(node_end_id),
+            ) {
+                (*start, *end)
+            } else {
+                return;
+            };
+            let line = LineSegment::new(node_start.site, node_end.site);
+
+            let crossing = self
+                .path_network
+                .paths_touching_rect_iter(node_start.site, node_end.site)
+                .filter(|(id_a, id_b)| {
+                    ![node_start_id, node_end_id].contains(id_a)
+                        && ![node_start_id, node_end_id].contains(id_b)
+                })
+                .filter_map(|(id_a, id_b)| {
+                    let node_a = self.path_network.get_node(*id_a)?;
+                    let node_b = self.path_network.get_node(*id_b)?;
+                    if node_a.path_creates_bridge(node_b) {
+                        return None;
+                    }
+                    let site =
+                        LineSegment::new(node_a.site, node_b.site).get_intersection(&line)?;
+                    Some((site, (*node_a, *id_a), (*node_b, *id_b)))
+                })
+                .min_by(|a, b| {
+                    a.0.distance_2(&node_start.site)
+                        .total_cmp(&b.0.distance_2(&node_start.site))
+                });
+
+            let (site, (node_a, id_a), (node_b, id_b)) = if let Some(crossing) = crossing {
+                crossing
+            } else {
+                self.add_path(node_start_id, node_end_id);
+                return;
+            };
+
+            let crossing_node_id = if site == node_a.site {
+                id_a
+            } else if site == node_b.site {
+                id_b
+            } else {
+                let crossing_node_id = self.add_node(
+                    TransportNode::new(
+                        site,
+                        node_a.elevation_on_path(&node_b, site),
+                        node_a.path_stage(&node_b),
+                        false,
+                    ),
+                    Some(node_start_id),
+                );
+                self.remove_path(id_a, id_b);
+                self.add_path(id_a, crossing_node_id);
+                self.add_path(crossing_node_id, id_b);
+                crossing_node_id
+            };
+            self.add_path(node_start_id, crossing_node_id);
+            node_start_id = crossing_node_id;
+        }
     }
 
     /// Iterate the path network `n` times.
@@ -137,40 +771,106 @@ where
         self
     }
 
-    fn determine_growth_from_stump(&self, stump: &Stump) -> Option<GrowthTypes> {
-        let stump_node = self.path_network.get_node(stump.get_node_id())?;
+    fn determine_growth_from_stump(&self, stump: &Stump<M>) -> Option<GrowthTypes> {
+        Self::determine_growth(&self.path_network, &self.constraints, stump)
+    }
+
+    /// Determine the growth of the stump, only reading the network.
+    fn determine_growth(
+        path_network: &PathNetwork<TransportNode>,
+        constraints: &GrowthConstraints,
+        stump: &Stump<M>,
+    ) -> Option<GrowthTypes> {
+        let stump_node = path_network.get_node(stump.get_node_id())?;
 
         // Find nodes around the line from the start site to the expected end site.
-        let related_nodes = self
-            .path_network
+        let related_nodes = path_network
             .nodes_around_line_iter(
                 LineSegment::new(stump_node.site, stump.get_node_expected_end().site),
                 stump.get_rules().path_extra_length_for_intersection,
             )
             .filter(|&node_id| *node_id != stump.get_node_id())
-            .filter_map(|node_id| Some((self.path_network.get_node(*node_id)?, *node_id)))
+            .filter_map(|node_id| Some((path_network.get_node(*node_id)?, *node_id)))
             .collect::<Vec<_>>();
 
         // Find paths touching the rectangle around the line.
-        let related_paths = self
-            .path_network
+        let related_paths = path_network
             .paths_touching_rect_iter(stump_node.site, stump.get_node_expected_end().site)
             .filter(|(node_id_start, node_id_end)| {
                 *node_id_start != stump.get_node_id() && *node_id_end != stump.get_node_id()
             })
             .filter_map(|(node_id_start, node_id_end)| {
-                let node_start = self.path_network.get_node(*node_id_start)?;
-                let node_end = self.path_network.get_node(*node_id_end)?;
+                let node_start = path_network.get_node(*node_id_start)?;
+                let node_end = path_network.get_node(*node_id_end)?;
                 Some(((node_start, *node_id_start), (node_end, *node_id_end)))
             })
             .collect::<Vec<_>>();
 
+        // Find sites of nodes adjacent to the start node and related nodes to check the angles between paths.
+        let neighbor_sites = if stump.get_rules().junction_rules.min_angle > 0.0 {
+            related_nodes
+                .iter()
+                .map(|(_, node_id)| *node_id)
+                .chain(std::iter::once(stump.get_node_id()))
+                .filter_map(|node_id| {
+                    let sites = path_network
+                        .neighbors_iter(node_id)?
+                        .map(|(_, node)| node.site)
+                        .collect::<Vec<_>>();
+                    Some((node_id, sites))
+                })
+                .collect::<BTreeMap<_, _>>()
+        } else {
+            BTreeMap::new()
+        };
+
+        // Find junctions around related nodes and nodes of related paths to check the spacing between intersections.
+        let spacing = stump.get_rules().junction_rules.min_intersection_spacing;
+        let junction_distances = if spacing > 0.0 {
+            related_nodes
+                .iter()
+                .chain(related_paths.iter().flat_map(|(start, end)| [start, end]))
+                .map(|(_, node_id)| *node_id)
+                .collect::<BTreeSet<_>>()
+                .into_iter()
+                .filter_map(|node_id| {
+                    Some((
+                        node_id,
+                        Self::junction_distance(path_network, node_id, spacing)?,
+                    ))
+                })
+                .collect::<BTreeMap<_, _>>()
+        } else {
+            BTreeMap::new()
+        };
+
         // Determine the growth of the path.
-        let growth = stump.determine_growth(stump_node, &related_nodes, &related_paths);
+        let growth = stump.determine_growth(
+            stump_node,
+            &related_nodes,
+            &related_paths,
+            &neighbor_sites,
+            &junction_distances,
+            constraints,
+        );
 
         Some(growth)
     }
 
+    /// Check if there are junctions (nodes with more than two paths) within the distance from the site.
+    fn has_junction_around(&self, site: Site, distance: f64) -> bool {
+        if distance <= 0.0 {
+            return false;
+        }
+        self.path_network
+            .nodes_around_site_iter(site, distance)
+            .any(|node_id| {
+                self.path_network
+                    .neighbors_iter(*node_id)
+                    .is_some_and(|neighbors| neighbors.count() > 2)
+            })
+    }
+
     /// Iterate the path network to the next step.
     pub fn iterate<R>(mut self, rng: &mut R) -> Self
     where
@@ -197,20 +897,134 @@ where
         )
     }
 
+    /// Iterate the path network evaluating a batch of stumps in parallel.
+    ///
+    /// Up to `batch_size` stumps with the highest priorities whose search regions don't overlap
+    /// are popped, their growths are determined concurrently, and they are applied in priority order.
+    /// The result is identical to `iterate` called the same number of times: a growth is determined again
+    /// if the network around it is modified by the preceding growths, and the rest of the batch is returned
+    /// to the heap if a stump pushed by the preceding growths has a higher priority.
+    #[cfg(feature = "rayon")]
+    pub fn iterate_parallel<R>(mut self, batch_size: usize, rng: &mut R) -> Self
+    where
+        R: RandomF64Provider,
+        M: Send + Sync,
+    {
+        use rayon::prelude::*;
+
+        let mut batch: Vec<(Stump<M>, Option<Bounds>)> = Vec::new();
+        while batch.len() < batch_size.max(1) {
+            let stump = if let Some(stump) = self.stump_heap.pop() {
+                stump
+            } else {
+                break;
+            };
+            let bounds = self
+                .path_network
+                .get_node(stump.get_node_id())
+                .map(|node| stump.search_bounds(node.site));
+            let overlaps = batch.iter().any(|(_, other)| {
+                if let (Some((min, max)), Some((other_min, other_max))) = (bounds, other) {
+                    min.x <= other_max.x
+                        && other_min.x <= max.x
+                        && min.y <= other_max.y
+                        && other_min.y <= max.y
+                } else {
+                    false
+                }
+            });
+            if overlaps {
+                self.stump_heap.push(stump);
+                break;
+            }
+            batch.push((stump, bounds));
+        }
+
+        let (path_network, constraints) = (&self.path_network, &self.constraints);
+        let growths = batch
+            .par_iter()
+            .map(|(stump, _)| Self::determine_growth(path_network, constraints, stump))
+            .collect::<Vec<_>>();
+
+        self.modified_bounds = Some(Vec::new());
+        let mut batch = batch.into_iter().zip(growths);
+        while let Some(((stump, bounds), growth)) = batch.next() {
+            // stumps pushed by the preceding growths are popped first in sequential iterations.
+            if self.stump_heap.peek().is_some_and(|top| *top > stump) {
+                self.stump_heap.push(stump);
+                self.stump_heap.extend(batch.map(|((stump, _), _)| stump));
+                break;
+            }
+            let growth = if bounds.is_some_and(|bounds| self.is_modified_within(bounds)) {
+                self.determine_growth_from_stump(&stump)
+            } else {
+                growth
+            };
+            if let Some(growth) = growth {
+                self = self.apply_next_growth(
+                    rng,
+                    growth.next_node,
+                    growth.bridge_node,
+                    stump.get_node_id(),
+                    &stump,
+                );
+            }
+        }
+        self.modified_bounds = None;
+
+        self
+    }
+
+    /// Iterate network generation in parallel until there are no more stumps (see `iterate_parallel`).
+    #[cfg(feature = "rayon")]
+    pub fn iterate_as_possible_parallel<R>(mut self, batch_size: usize, rng: &mut R) -> Self
+    where
+        R: RandomF64Provider,
+        M: Send + Sync,
+    {
+        while !self.stump_heap.is_empty() {
+            self = self.iterate_parallel::<R>(batch_size, rng);
+        }
+        self
+    }
+
+    /// Get the metrics of the stump extended by the path to the new node.
+    fn extended_metrics(&self, stump: &Stump<M>, node_next: TransportNode) -> M {
+        let mut metrics = stump.get_metrics().clone();
+        if let Some(node_start) = self.path_network.get_node(stump.get_node_id()) {
+            metrics.on_extend(&PathExtension {
+                start: *node_start,
+                end: node_next,
+                creates_bridge: stump.creates_bridge(),
+            });
+        }
+        metrics
+    }
+
+    /// Get the metrics of the branch from the metrics of the parent path.
+    fn branched_metrics(metrics: &M, staging: bool) -> M {
+        let mut metrics = metrics.clone();
+        metrics.on_branch();
+        if staging {
+            metrics.on_stage();
+        }
+        metrics
+    }
+
     fn apply_next_growth<R>(
         mut self,
         rng: &mut R,
         next_node_type: NextNodeType,
         bridge_node_type: BridgeNodeType,
         stump_node_id: NodeId,
-        stump: &Stump,
+        stump: &Stump<M>,
     ) -> Self
     where
         R: RandomF64Provider,
     {
         if let BridgeNodeType::Middle(bridge_node) = bridge_node_type {
-            let bridge_node_id = self.path_network.add_node(bridge_node);
-            self.path_network.add_path(stump_node_id, bridge_node_id);
+            let bridge_node_id = self.add_node_from(bridge_node, stump_node_id);
+            self.add_path(stump_node_id, bridge_node_id);
 
             return self.apply_next_growth(
                 rng,
@@ -232,32 +1046,66 @@ where
                 return self;
             }
             NextNodeType::Existing(node_id) => {
-                self.path_network.add_path(stump_node_id, node_id);
+                self.add_path(stump_node_id, node_id);
             }
             NextNodeType::Intersect(node_next, encount_path) => {
-                let next_node_id = self.path_network.add_node(node_next);
-                self.path_network
-                    .remove_path(encount_path.0, encount_path.1);
-                self.path_network.add_path(stump_node_id, next_node_id);
-                self.path_network.add_path(next_node_id, encount_path.0);
-                self.path_network.add_path(next_node_id, encount_path.1);
+                let next_node_id = self.add_node_from(node_next, stump_node_id);
+                self.remove_path(encount_path.0, encount_path.1);
+                self.add_path(stump_node_id, next_node_id);
+                self.add_path(next_node_id, encount_path.0);
+                self.add_path(next_node_id, encount_path.1);
             }
             NextNodeType::New(node_next) => {
-                let node_id = self.path_network.add_node(node_next);
-                self.path_network.add_path(stump_node_id, node_id);
+                let node_id = self.add_node_from(node_next, stump_node_id);
+                self.add_path(stump_node_id, node_id);
+
+                // the path clipped at the boundary is not extended anymore.
+                if stump.is_clipped() {
+                    return self;
+                }
+
+                let metrics = self.extended_metrics(stump, node_next);
 
                 let straight_angle = start_site.get_angle(&node_next.site);
-                self.push_new_stump(
-                    node_id,
-                    straight_angle,
-                    stump.get_stage(),
-                    stump.get_metrics().incremented(false, false),
-                );
-                let clockwise_branch =
-                    rng.gen_f64() < stump.get_rules().branch_rules.branch_density;
+                if let Some(switchback) = stump.get_switchback() {
+                    self.push_switchback_stump(
+                        node_id,
+                        switchback,
+                        start_site.distance(&node_next.site),
+                        stump.get_stage(),
+                        metrics.clone(),
+                    );
+                } else {
+                    self.push_new_stump(
+                        node_id,
+                        straight_angle,
+                        stump.get_stage(),
+                        metrics.clone(),
+                    );
+                }
+                // the random values are derived from the path if the builder is seeded per path.
+                let mut path_random = self
+                    .path_seed
+                    .map(|seed| PathRandom::new(seed, start_site, node_next.site));
+                let mut gen_f64 = || match path_random.as_mut() {
+                    Some(path_random) => path_random.gen_f64(),
+                    None => rng.gen_f64(),
+                };
+
+                // branches are not created near other junctions.
+                let branch_density = if self.has_junction_around(
+                    node_next.site,
+                    stump.get_rules().junction_rules.min_intersection_spacing,
+                ) {
+                    0.0
+                } else {
+                    stump.get_rules().branch_rules.branch_density
+                };
+
+                let clockwise_branch = gen_f64() < branch_density;
                 if clockwise_branch {
                     let clockwise_staging =
-                        rng.gen_f64() < stump.get_rules().branch_rules.staging_probability;
+                        gen_f64() < stump.get_rules().branch_rules.staging_probability;
                     let next_stage = if clockwise_staging {
                         stump.get_stage().incremented()
                     } else {
@@ -267,15 +1115,14 @@ where
                         node_id,
                         straight_angle.right_clockwise(),
                         next_stage,
-                        stump.get_metrics().incremented(clockwise_staging, true),
+                        Self::branched_metrics(&metrics, clockwise_staging),
                     );
                 }
 
-                let counterclockwise_branch =
-                    rng.gen_f64() < stump.get_rules().branch_rules.branch_density;
+                let counterclockwise_branch = gen_f64() < branch_density;
                 if counterclockwise_branch {
                     let counterclockwise_staging =
-                        rng.gen_f64() < stump.get_rules().branch_rules.staging_probability;
+                        gen_f64() < stump.get_rules().branch_rules.staging_probability;
                     let next_stage = if counterclockwise_staging {
                         stump.get_stage().incremented()
                     } else {
@@ -285,9 +1132,7 @@ where
                         node_id,
                         straight_angle.right_counterclockwise(),
                         next_stage,
-                        stump
-                            .get_metrics()
-                            .incremented(counterclockwise_staging, true),
+                        Self::branched_metrics(&metrics, counterclockwise_staging),
                     );
                 }
             }
@@ -296,7 +1141,620 @@ where
         self
     }
 
+    /// Fill blocks (regions enclosed by paths) larger than the maximum area with new paths.
+    ///
+    /// In each round, the longest boundary path of each oversized block is split at the middle,
+    /// and a new path grows from the split node into the block until the growth stops.
+    /// Rounds are repeated until all blocks fall under the maximum area, or `max_rounds` times.
+    pub fn iterate_infill<R, BP>(
+        mut self,
+        block_area_provider: &BP,
+        max_rounds: usize,
+        rng: &mut R,
+    ) -> Self
+    where
+        R: RandomF64Provider,
+        BP: BlockAreaProvider,
+    {
+        for _ in 0..max_rounds {
+            let oversized_blocks = self
+                .path_network
+                .faces()
+                .into_iter()
+                .filter_map(|face| {
+                    let polygon = Polygon::new(
+                        face.iter()
+                            .filter_map(|node_id| Some(self.path_network.get_node(*node_id)?.site))
+                            .collect(),
+                    );
+                    let count = polygon.vertices().len() as f64;
+                    let center = polygon
+                        .vertices()
+                        .iter()
+                        .fold(Site::new(0.0, 0.0), |sum, site| {
+                            Site::new(sum.x + site.x / count, sum.y + site.y / count)
+                        });
+                    let max_area = block_area_provider.get_max_block_area(&center)?;
+                    (polygon.area() > max_area).then_some((face, polygon))
+                })
+                .collect::<Vec<_>>();
+
+            let stump_count = oversized_blocks
+                .iter()
+                .filter_map(|(face, polygon)| self.push_infill_stump(face, polygon))
+                .count();
+            if stump_count == 0 {
+                break;
+            }
+
+            self = self.iterate_as_possible(rng);
+        }
+        self
+    }
+
+    /// Split the longest boundary path of the block and add a path stump heading into the block.
+    fn push_infill_stump(&mut self, face: &[NodeId], polygon: &Polygon) -> Option<()> {
+        let (start_id, end_id) = (0..face.len())
+            .map(|i| (face[i], face[(i + 1) % face.len()]))
+            .filter_map(|(start_id, end_id)| {
+                let start = self.path_network.get_node(start_id)?;
+                let end = self.path_network.get_node(end_id)?;
+                // the path may be already split by the other block.
+                if start.is_bridge || end.is_bridge || !self.path_network.has_path(start_id, end_id)
+                {
+                    return None;
+                }
+                Some(((start_id, end_id), start.site.distance_2(&end.site)))
+            })
+            .max_by(|a, b| a.1.total_cmp(&b.1))?
+            .0;
+
+        let start = *self.path_network.get_node(start_id)?;
+        let end = *self.path_network.get_node(end_id)?;
+        let site_middle = start.site.midpoint(&end.site);
+        let stage = start.path_stage(&end);
+
+        // the block is on the side where the site slightly moved from the middle is inside the block.
+        let angle_path = start.site.get_angle(&end.site);
+        let probe_distance = start.site.distance(&end.site) * 1e-3;
+        let angle_inward = [
+            angle_path.right_clockwise(),
+            angle_path.right_counterclockwise(),
+        ]
+        .into_iter()
+        .find(|angle| polygon.contains(&site_middle.extend(*angle, probe_distance)))?;
+
+        let middle_id = self.add_node_from(
+            TransportNode::new(
+                site_middle,
+                start.elevation_on_path(&end, site_middle),
+                stage,
+                false,
+            ),
+            start_id,
+        );
+        self.remove_path(start_id, end_id);
+        self.add_path(start_id, middle_id);
+        self.add_path(middle_id, end_id);
+
+        if self
+            .push_new_stump(
+                middle_id,
+                angle_inward,
+                stage,
+                Self::branched_metrics(&M::initial(), false),
+            )
+            .is_none()
+        {
+            // restore the path if no path can grow from the middle.
+            self.remove_path(start_id, middle_id);
+            self.remove_path(middle_id, end_id);
+            self.remove_node(middle_id);
+            self.add_path(start_id, end_id);
+            return None;
+        }
+        Some(())
+    }
+
+    /// Replace qualifying junctions with roundabouts.
+    ///
+    /// `predicate` receives the junction and its neighbors, and can reject the junction.
+    /// Junctions whose ring doesn't fit the terrain or collides with other paths are left as they are.
+    pub fn place_roundabouts<F>(mut self, rules: &RoundaboutRules, predicate: F) -> Self
+    where
+        F: Fn(&TransportNode, &[TransportNode]) -> bool,
+    {
+        roundabout::place_roundabouts(
+            &mut self.path_network,
+            self.terrain_provider,
+            rules,
+            predicate,
+        );
+        self.sync_with_network();
+        self
+    }
+
+    /// Treat dead ends (nodes with only one path) by pruning, connecting, or converting into cul-de-sacs.
+    pub fn treat_dead_ends(mut self, rules: &DeadEndRules) -> Self {
+        dead_end::treat_dead_ends(&mut self.path_network, self.terrain_provider, rules);
+        self.sync_with_network();
+        self
+    }
+
     pub fn snapshot(self) -> (Option<PathNetwork<TransportNode>>, Self) {
         (self.path_network.clone().reconstruct(), self)
     }
+
+    /// Take a snapshot of the network with the payloads of the nodes.
+    ///
+    /// The payloads are keyed by the node ids of the reconstructed network.
+    /// If no payload is attached, the map is empty.
+    #[allow(clippy::type_complexity)]
+    pub fn snapshot_with_payloads(
+        self,
+    ) -> (
+        Option<(PathNetwork<TransportNode>, BTreeMap<NodeId, P>)>,
+        Self,
+    )
+    where
+        P: Clone,
+    {
+        let snapshot =
+            self.path_network
+                .clone()
+                .reconstruct_with_id_map()
+                .map(|(network, id_map)| {
+                    let payloads = self
+                        .node_payloads
+                        .as_ref()
+                        .map(|node_payloads| {
+                            node_payloads
+                                .payloads()
+                                .iter()
+                                .filter_map(|(node_id, payload)| {
+                                    Some((*id_map.get(node_id)?, payload.clone()))
+                                })
+                                .collect()
+                        })
+                        .unwrap_or_default();
+                    (network, payloads)
+                });
+        (snapshot, self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::transport::{
+        combinator::{
+            FnPathPrioritizator, FnRandomF64Provider, FnRulesProvider, FnTerrainProvider,
+        },
+        params::{
+            priority::PathPrioritizationFactors,
+            rules::{
+                branch::BranchRules, clearance::ClearanceRules, direction::PathDirectionRules,
+                switchback::SwitchbackRules, ElevationDiffLimit,
+            },
+        },
+        planner::backbone::BackboneType,
+    };
+
+    use super::*;
+
+    fn rules_provider() -> FnRulesProvider<impl Fn(&GrowthContext) -> Option<TransportRules>> {
+        FnRulesProvider(|_: &GrowthContext| {
+            Some(
+                TransportRules::default()
+                    .path_normal_length(1.0)
+                    .path_extra_length_for_intersection(0.3)
+                    .path_direction_rules(PathDirectionRules {
+                        max_radian: 0.2,
+                        comparison_step: 3,
+                        ..Default::default()
+                    })
+                    .branch_rules(BranchRules {
+                        branch_density: 0.4,
+                        staging_probability: 0.1,
+                    }),
+            )
+        })
+    }
+
+    fn terrain_provider() -> FnTerrainProvider<impl Fn(&Site) -> Option<f64>> {
+        FnTerrainProvider(|site: &Site| Some(site.x * 0.1))
+    }
+
+    fn square(size: f64) -> Polygon {
+        Polygon::new(vec![
+            Site::new(-size, -size),
+            Site::new(size, -size),
+            Site::new(size, size),
+            Site::new(-size, size),
+        ])
+    }
+
+    fn rng(seed: u64) -> FnRandomF64Provider<impl FnMut() -> f64> {
+        let mut state = seed;
+        FnRandomF64Provider(move || {
+            state = state
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            (state >> 11) as f64 / (1u64 << 53) as f64
+        })
+    }
+
+    /// Get the paths of the network in both directions, sorted by the sites.
+    fn paths(network: &PathNetwork<TransportNode>) -> Vec<(Site, Site)> {
+        let mut paths = network
+            .nodes_iter()
+            .flat_map(|(node_id, node)| {
+                network
+                    .neighbors_iter(node_id)
+                    .into_iter()
+                    .flatten()
+                    .map(move |(_, neighbor)| (node.site, neighbor.site))
+            })
+            .collect::<Vec<_>>();
+        paths.sort_by(|(a0, a1), (b0, b1)| {
+            (a0.x, a0.y, a1.x, a1.y)
+                .partial_cmp(&(b0.x, b0.y, b1.x, b1.y))
+                .unwrap()
+        });
+        paths
+    }
+
+    #[test]
+    fn test_seed_per_path_keeps_edits_local() {
+        let (rules_provider, terrain_provider) = (rules_provider(), terrain_provider());
+        let origins = [Site::new(0.0, 0.0), Site::new(30.0, 0.0)];
+        // paths grow only around the origins.
+        let path_prioritizator = FnPathPrioritizator(|factors: PathPrioritizationFactors| {
+            let distance = origins
+                .iter()
+                .map(|origin| factors.site_end.distance(origin))
+                .fold(f64::INFINITY, f64::min);
+            (distance < 5.0).then_some(-distance)
+        });
+
+        let build = |origins: &[Site]| {
+            let builder =
+                TransportBuilder::new(&rules_provider, &terrain_provider, &path_prioritizator)
+                    .set_boundary(square(40.0))
+                    .seed_per_path(7);
+            let builder = origins.iter().fold(builder, |builder, origin| {
+                builder.add_origin(*origin, 0.0, None).unwrap()
+            });
+            paths(
+                &builder
+                    .iterate_as_possible(&mut rng(1))
+                    .snapshot()
+                    .0
+                    .unwrap(),
+            )
+        };
+
+        let single = build(&origins[..1]);
+        assert!(single.len() > 20);
+        // the network around the first origin is not changed by the second origin.
+        let both = build(&origins)
+            .into_iter()
+            .filter(|(start, _)| start.x < 15.0)
+            .collect::<Vec<_>>();
+        assert_eq!(both, single);
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn test_parallel_iteration_is_identical_to_sequential() {
+        let (rules_provider, terrain_provider) = (rules_provider(), terrain_provider());
+        // equal priorities in each ring around the origin.
+        let path_prioritizator = FnPathPrioritizator(|factors: PathPrioritizationFactors| {
+            Some(-(factors.site_end.distance(&Site::new(0.0, 0.0))).floor())
+        });
+
+        let build = |parallel: bool| {
+            let mut rng = rng(1);
+            let builder =
+                TransportBuilder::new(&rules_provider, &terrain_provider, &path_prioritizator)
+                    .set_boundary(square(8.0))
+                    .add_origin(Site::new(0.0, 0.0), 0.0, None)
+                    .unwrap();
+            let builder = if parallel {
+                builder.iterate_as_possible_parallel(8, &mut rng)
+            } else {
+                builder.iterate_as_possible(&mut rng)
+            };
+            paths(&builder.snapshot().0.unwrap())
+        };
+
+        let sequential = build(false);
+        assert!(sequential.len() > 20);
+        assert_eq!(build(true), sequential);
+    }
+
+    struct ConstantBlockArea(f64);
+
+    impl BlockAreaProvider for ConstantBlockArea {
+        fn get_max_block_area(&self, _: &Site) -> Option<f64> {
+            Some(self.0)
+        }
+    }
+
+    #[test]
+    fn test_infill_splits_only_oversized_blocks() {
+        let (rules_provider, terrain_provider) = (rules_provider(), terrain_provider());
+        let path_prioritizator = FnPathPrioritizator(|_: PathPrioritizationFactors| Some(0.0));
+        let planner = HighwayPlanner::default()
+            .backbone_type(BackboneType::RelativeNeighborhoodGraph)
+            .rules(TransportRules::default().path_normal_length(1.0));
+
+        // a large block (area 196) and a small block (area 9) enclosed by highways.
+        let block = |origin: Site, size: f64| {
+            [
+                origin,
+                Site::new(origin.x + size, origin.y),
+                Site::new(origin.x + size, origin.y + size),
+                Site::new(origin.x, origin.y + size),
+            ]
+        };
+        let builder =
+            TransportBuilder::new(&rules_provider, &terrain_provider, &path_prioritizator)
+                .add_highways(&block(Site::new(0.0, 0.0), 14.0), &planner)
+                .add_highways(&block(Site::new(30.0, 0.0), 3.0), &planner);
+        let (before, builder) = builder.snapshot();
+        let before = paths(&before.unwrap());
+        let after = paths(
+            &builder
+                .iterate_infill(&ConstantBlockArea(50.0), 1, &mut rng(1))
+                .snapshot()
+                .0
+                .unwrap(),
+        );
+
+        let within = |paths: &[(Site, Site)], x_min: f64, x_max: f64| {
+            paths
+                .iter()
+                .filter(|(start, _)| x_min <= start.x && start.x <= x_max)
+                .cloned()
+                .collect::<Vec<_>>()
+        };
+        assert!(within(&after, -1.0, 15.0).len() > within(&before, -1.0, 15.0).len());
+        assert_eq!(within(&after, 29.0, 34.0), within(&before, 29.0, 34.0));
+    }
+
+    /// Count the nodes where the path turns back with an acute angle.
+    fn hairpins(network: &PathNetwork<TransportNode>) -> usize {
+        network
+            .nodes_iter()
+            .filter(|(node_id, node)| {
+                let neighbors = network
+                    .neighbors_iter(*node_id)
+                    .into_iter()
+                    .flatten()
+                    .map(|(_, neighbor)| node.site.get_angle(&neighbor.site))
+                    .collect::<Vec<_>>();
+                neighbors.len() == 2
+                    && neighbors[0].diff(&neighbors[1]) < 0.5 * std::f64::consts::PI
+            })
+            .count()
+    }
+
+    #[test]
+    fn test_switchback_only_on_steep_slopes() {
+        let rules_provider = FnRulesProvider(|_: &GrowthContext| {
+            Some(
+                TransportRules::default()
+                    .path_normal_length(1.0)
+                    .path_extra_length_for_intersection(0.3)
+                    .path_slope_elevation_diff_limit(ElevationDiffLimit::Linear(0.5))
+                    .path_direction_rules(PathDirectionRules {
+                        max_radian: 0.2,
+                        comparison_step: 3,
+                        ..Default::default()
+                    })
+                    .switchback_rules(SwitchbackRules {
+                        max_hairpins: 2,
+                        min_hairpin_spacing: 2.0,
+                        check_step: 3,
+                    }),
+            )
+        });
+        let path_prioritizator = FnPathPrioritizator(|_: PathPrioritizationFactors| Some(0.0));
+        let build = |terrain_provider: &FnTerrainProvider<fn(&Site) -> Option<f64>>,
+                     zone: Option<KeepOutZone>| {
+            let builder =
+                TransportBuilder::new(&rules_provider, terrain_provider, &path_prioritizator)
+                    .set_boundary(square(8.0));
+            let builder = if let Some(zone) = zone {
+                builder.add_keep_out_zone(zone)
+            } else {
+                builder
+            };
+            builder
+                .add_origin(Site::new(0.0, 0.0), 0.0, None)
+                .unwrap()
+                .iterate_as_possible(&mut rng(1))
+                .snapshot()
+                .0
+                .unwrap()
+        };
+
+        // the slope along the heading is 1.0, which exceeds the limit.
+        let steep = FnTerrainProvider((|site: &Site| Some(-site.y)) as fn(&Site) -> Option<f64>);
+        let network = build(&steep, None);
+        assert!(hairpins(&network) > 0);
+        assert!(network.nodes_iter().any(|(_, node)| node.site.y < -2.0));
+
+        // the path blocked by the keep-out zone doesn't start a switchback sequence.
+        let flat = FnTerrainProvider((|_: &Site| Some(0.0)) as fn(&Site) -> Option<f64>);
+        let zone = KeepOutZone::new(Polygon::new(vec![
+            Site::new(-0.5, -3.0),
+            Site::new(0.5, -3.0),
+            Site::new(0.5, -0.5),
+            Site::new(-0.5, -0.5),
+        ]));
+        let network = build(&flat, Some(zone));
+        assert_eq!(hairpins(&network), 0);
+        assert!(network.nodes_iter().all(|(_, node)| node.site.y >= 0.0));
+    }
+
+    #[test]
+    fn test_directed_origin_grows_one_way() {
+        let rules_provider = FnRulesProvider(|_: &GrowthContext| {
+            Some(TransportRules::default().path_normal_length(1.0))
+        });
+        let terrain_provider = terrain_provider();
+        let path_prioritizator = FnPathPrioritizator(|_: PathPrioritizationFactors| Some(0.0));
+        let builder = || {
+            TransportBuilder::new(&rules_provider, &terrain_provider, &path_prioritizator)
+                .set_boundary(square(8.0))
+        };
+        let min_x = |builder: TransportBuilder<_, _, _>| {
+            builder
+                .iterate_as_possible(&mut rng(1))
+                .snapshot()
+                .0
+                .unwrap()
+                .nodes_iter()
+                .map(|(_, node)| node.site.x)
+                .fold(f64::INFINITY, f64::min)
+        };
+
+        let directed = builder()
+            .add_directed_origin(Site::new(0.0, 0.0), std::f64::consts::FRAC_PI_2, None)
+            .unwrap();
+        assert_eq!(min_x(directed), 0.0);
+        let both = builder()
+            .add_origin(Site::new(0.0, 0.0), std::f64::consts::FRAC_PI_2, None)
+            .unwrap();
+        assert!(min_x(both) < -7.0);
+
+        // the origin outside of the boundary is not placed.
+        assert!(builder()
+            .add_directed_origin(Site::new(10.0, 0.0), 0.0, None)
+            .is_none());
+    }
+
+    #[test]
+    fn test_origin_metrics() {
+        let recorded = std::cell::RefCell::new(vec![]);
+        let rules_provider = FnRulesProvider(|context: &GrowthContext| {
+            recorded.borrow_mut().push(context.metrics.clone());
+            Some(TransportRules::default().path_normal_length(1.0))
+        });
+        let terrain_provider = terrain_provider();
+        let path_prioritizator = FnPathPrioritizator(|_: PathPrioritizationFactors| Some(0.0));
+        TransportBuilder::new(&rules_provider, &terrain_provider, &path_prioritizator)
+            .add_origin(Site::new(0.0, 0.0), 0.0, None)
+            .unwrap();
+
+        // the paths from the origin are counted as extended once.
+        let expected = PathMetrics {
+            extend_count: 1,
+            extend_count_since_last_staged: 1,
+            extend_count_since_last_branched: 1,
+            branch_count: 0,
+        };
+        assert_eq!(recorded.into_inner(), vec![expected.clone(), expected]);
+    }
+
+    #[test]
+    fn test_growth_is_pulled_toward_attractor() {
+        let rules_provider = FnRulesProvider(|_: &GrowthContext| {
+            Some(
+                TransportRules::default()
+                    .path_normal_length(1.0)
+                    .path_extra_length_for_intersection(0.3)
+                    .path_direction_rules(PathDirectionRules {
+                        max_radian: 0.6,
+                        comparison_step: 5,
+                        ..Default::default()
+                    }),
+            )
+        });
+        let terrain_provider = terrain_provider();
+        // the straight path is preferred.
+        let path_prioritizator = FnPathPrioritizator(|factors: PathPrioritizationFactors| {
+            let angle = factors.site_start.get_angle(&factors.site_end);
+            Some(-factors.context.heading.diff(&angle))
+        });
+        let attractor_site = Site::new(7.0, 4.0);
+        let build = |attractor: Option<Attractor>| {
+            let builder =
+                TransportBuilder::new(&rules_provider, &terrain_provider, &path_prioritizator)
+                    .set_boundary(square(10.0));
+            let builder = if let Some(attractor) = attractor {
+                builder.add_attractor(attractor)
+            } else {
+                builder
+            };
+            builder
+                .add_directed_origin(Site::new(0.0, 0.0), std::f64::consts::FRAC_PI_2, None)
+                .unwrap()
+                .iterate_as_possible(&mut rng(1))
+                .snapshot()
+                .0
+                .unwrap()
+        };
+        let max_y = |network: &PathNetwork<TransportNode>| {
+            network
+                .nodes_iter()
+                .map(|(_, node)| node.site.y)
+                .fold(f64::NEG_INFINITY, f64::max)
+        };
+
+        let straight = build(None);
+        assert!(max_y(&straight) < 1e-6);
+
+        // the path curves toward the attractor and is snapped to it.
+        let attracted = build(Some(Attractor::new(attractor_site, 20.0, 10.0)));
+        assert!(attracted
+            .nodes_iter()
+            .any(|(_, node)| node.site == attractor_site));
+        // the reached attractor doesn't pull the path back anymore.
+        assert!(attracted
+            .nodes_iter()
+            .any(|(_, node)| node.site.x > attractor_site.x + 1.5));
+    }
+
+    #[test]
+    fn test_clearance_longer_than_path() {
+        let rules_provider = FnRulesProvider(|_: &GrowthContext| {
+            Some(
+                TransportRules::default()
+                    .path_normal_length(1.0)
+                    .clearance_rules(ClearanceRules {
+                        min_parallel_clearance: 2.5,
+                        parallel_max_radian: 0.3,
+                    }),
+            )
+        });
+        let terrain_provider = terrain_provider();
+        let path_prioritizator = FnPathPrioritizator(|_: PathPrioritizationFactors| Some(0.0));
+        let (network, builder) =
+            TransportBuilder::new(&rules_provider, &terrain_provider, &path_prioritizator)
+                .set_boundary(square(8.0))
+                .add_directed_origin(Site::new(0.0, 0.0), std::f64::consts::FRAC_PI_2, None)
+                .unwrap()
+                .iterate_as_possible(&mut rng(1))
+                .snapshot();
+
+        // the path is not blocked by the paths which it has grown along.
+        assert!(network
+            .unwrap()
+            .nodes_iter()
+            .any(|(_, node)| node.site.x > 7.0 && node.site.y.abs() < 1e-6));
+
+        // the parallel path is blocked.
+        let network = builder
+            .add_directed_origin(Site::new(0.0, 1.0), std::f64::consts::FRAC_PI_2, None)
+            .unwrap()
+            .iterate_as_possible(&mut rng(1))
+            .snapshot()
+            .0
+            .unwrap();
+        assert!(network
+            .nodes_iter()
+            .all(|(_, node)| node.site.y.abs() < 1e-6 || node.site.x == 0.0));
+    }
 }
@@ -0,0 +1,489 @@
+use std::{collections::BTreeMap, sync::Mutex};
+
+use crate::core::{
+    container::{lru::LruCache, path_network::NodeId},
+    geometry::site::Site,
+};
+
+use super::{
+    params::{metrics::GrowthMetrics, priority::PathPrioritizationFactors},
+    traits::{PathPrioritizator, TerrainProvider},
+};
+
+/// Default number of the entries held by the caches.
+const DEFAULT_CAPACITY: usize = 1 << 16;
+
+/// Quantization of the sites for the keys of the caches.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Quantizer {
+    /// the size of the cell which the sites are snapped to (`0.0` means exact sites).
+    resolution: f64,
+}
+
+type SiteKey = (i64, i64);
+
+impl Quantizer {
+    fn site(&self, site: &Site) -> SiteKey {
+        (self.value(site.x), self.value(site.y))
+    }
+
+    fn value(&self, value: f64) -> i64 {
+        if self.resolution > 0.0 {
+            (value / self.resolution).round() as i64
+        } else {
+            value.to_bits() as i64
+        }
+    }
+}
+
+/// Cache shared between the queries, with the number of hits and misses.
+#[derive(Debug)]
+struct SharedCache<K, V>
+where
+    K: Ord + Clone,
+    V: Clone,
+{
+    cache: Mutex<(LruCache<K, V>, CacheStats)>,
+}
+
+impl<K, V> SharedCache<K, V>
+where
+    K: Ord + Clone,
+    V: Clone,
+{
+    fn new(capacity: usize) -> Self {
+        Self {
+            cache: Mutex::new((LruCache::new(capacity), CacheStats::default())),
+        }
+    }
+
+    fn get_or_insert_with(&self, key: K, f: impl FnOnce() -> V) -> V {
+        {
+            let mut cache = self.cache.lock().unwrap();
+            if let Some(value) = cache.0.get(&key) {
+                cache.1.hits += 1;
+                return value;
+            }
+            cache.1.misses += 1;
+        }
+        // the provider is called without the lock so that it can be called concurrently.
+        let value = f();
+        self.cache.lock().unwrap().0.insert(key, value.clone());
+        value
+    }
+
+    /// Get the values of the keys, computing the missing values at once.
+    ///
+    /// `f` receives the indices of the first occurrences of the missing keys
+    /// and returns their values in the same order, so each missing key is computed once.
+    /// If `f` doesn't return one value per index, nothing is cached and
+    /// the missing values are `V::default()`.
+    fn get_or_insert_many(&self, keys: Vec<K>, f: impl FnOnce(&[usize]) -> Vec<V>) -> Vec<V>
+    where
+        V: Default,
+    {
+        let mut values = Vec::with_capacity(keys.len());
+        let mut missing = Vec::new();
+        // the position in `missing` of the key of each index to be computed.
+        let mut positions = BTreeMap::new();
+        let mut pending = Vec::new();
+        {
+            let mut cache = self.cache.lock().unwrap();
+            for (index, key) in keys.iter().enumerate() {
+                let value = cache.0.get(key);
+                if value.is_none() {
+                    let position = *positions.entry(key).or_insert_with(|| {
+                        missing.push(index);
+                        missing.len() - 1
+                    });
+                    pending.push((index, position));
+                }
+                values.push(value);
+            }
+            cache.1.hits += keys.len() - missing.len();
+            cache.1.misses += missing.len();
+        }
+        if missing.is_empty() {
+            return values.into_iter().flatten().collect();
+        }
+
+        let computed = f(&missing);
+        if computed.len() != missing.len() {
+            return values.into_iter().map(Option::unwrap_or_default).collect();
+        }
+        let mut cache = self.cache.lock().unwrap();
+        for (index, value) in missing.iter().zip(&computed) {
+            cache.0.insert(keys[*index].clone(), value.clone());
+        }
+        for (index, position) in pending {
+            values[index] = Some(computed[position].clone());
+        }
+        values.into_iter().flatten().collect()
+    }
+
+    fn stats(&self) -> CacheStats {
+        self.cache.lock().unwrap().1
+    }
+
+    fn clear(&self) {
+        let mut cache = self.cache.lock().unwrap();
+        cache.0.clear();
+        cache.1 = CacheStats::default();
+    }
+}
+
+/// Number of the queries answered from the cache (hits) and by the provider (misses).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct CacheStats {
+    pub hits: usize,
+    pub misses: usize,
+}
+
+/// `TerrainProvider` which memoizes the elevations of the wrapped provider.
+///
+/// The sites are quantized by the resolution, so the elevation of a site may be
+/// the one of a site within the resolution. By default the sites are not quantized.
+/// The least recently used elevations are evicted when the capacity is exceeded.
+#[derive(Debug)]
+pub struct CachedTerrainProvider<TP>
+where
+    TP: TerrainProvider,
+{
+    provider: TP,
+    quantizer: Quantizer,
+    elevations: SharedCache<SiteKey, Option<f64>>,
+}
+
+impl<TP> CachedTerrainProvider<TP>
+where
+    TP: TerrainProvider,
+{
+    /// Wrap the provider.
+    pub fn new(provider: TP) -> Self {
+        Self {
+            provider,
+            quantizer: Quantizer { resolution: 0.0 },
+            elevations: SharedCache::new(DEFAULT_CAPACITY),
+        }
+    }
+
+    /// Set the resolution of the quantization of the sites.
+    pub fn resolution(mut self, resolution: f64) -> Self {
+        self.quantizer = Quantizer { resolution };
+        self
+    }
+
+    /// Set the maximum number of the cached elevations.
+    pub fn capacity(mut self, capacity: usize) -> Self {
+        self.elevations = SharedCache::new(capacity);
+        self
+    }
+
+    /// Get the statistics of the cache.
+    pub fn stats(&self) -> CacheStats {
+        self.elevations.stats()
+    }
+
+    /// Remove all cached elevations (e.g. after the terrain is modified).
+    pub fn clear(&self) {
+        self.elevations.clear();
+    }
+
+    /// Get the wrapped provider.
+    pub fn into_inner(self) -> TP {
+        self.provider
+    }
+}
+
+impl<TP> TerrainProvider for CachedTerrainProvider<TP>
+where
+    TP: TerrainProvider,
+{
+    fn get_elevation(&self, site: &Site) -> Option<f64> {
+        self.elevations
+            .get_or_insert_with(self.quantizer.site(site), || {
+                self.provider.get_elevation(site)
+            })
+    }
+
+    fn get_elevations(&self, sites: &[Site]) -> Vec<Option<f64>> {
+        let keys = sites.iter().map(|site| self.quantizer.site(site)).collect();
+        self.elevations.get_or_insert_many(keys, |missing| {
+            let sites = missing
+                .iter()
+                .map(|&index| sites[index])
+                .collect::<Vec<_>>();
+            self.provider.get_elevations(&sites)
+        })
+    }
+
+    fn get_gradient(&self, site: &Site, delta: f64) -> Option<(f64, f64)> {
+        // the gradient of the wrapped provider may be more precise than central differences.
+        self.provider.get_gradient(site, delta)
+    }
+}
+
+/// Key of the cached priorities.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+struct PriorityKey {
+    node_id: NodeId,
+    heading: i64,
+    degree: usize,
+    nearest_path_distance: Option<i64>,
+    origin: Option<NodeId>,
+    site_start: SiteKey,
+    site_end: SiteKey,
+    path_length: i64,
+    stage: usize,
+    creates_bridge: bool,
+    road_density: Option<i64>,
+}
+
+/// `PathPrioritizator` which memoizes the priorities of the wrapped prioritizator.
+///
+/// The key of the cache is the factors and the fields of the context except the metrics and the path network.
+/// The metrics are generic and can't be a key, but they rarely differ between the paths
+/// from the same node with the same heading and stage.
+/// If the wrapped prioritizator reads the path network directly, `clear` the cache when the network changes.
+/// The sites and the distance to the nearest path are quantized in the same way as `CachedTerrainProvider`.
+#[derive(Debug)]
+pub struct CachedPathPrioritizator<PP> {
+    prioritizator: PP,
+    quantizer: Quantizer,
+    priorities: SharedCache<PriorityKey, Option<f64>>,
+}
+
+impl<PP> CachedPathPrioritizator<PP> {
+    /// Wrap the prioritizator.
+    pub fn new(prioritizator: PP) -> Self {
+        Self {
+            prioritizator,
+            quantizer: Quantizer { resolution: 0.0 },
+            priorities: SharedCache::new(DEFAULT_CAPACITY),
+        }
+    }
+
+    /// Set the resolution of the quantization of the sites.
+    pub fn resolution(mut self, resolution: f64) -> Self {
+        self.quantizer = Quantizer { resolution };
+        self
+    }
+
+    /// Set the maximum number of the cached priorities.
+    pub fn capacity(mut self, capacity: usize) -> Self {
+        self.priorities = SharedCache::new(capacity);
+        self
+    }
+
+    /// Get the statistics of the cache.
+    pub fn stats(&self) -> CacheStats {
+        self.priorities.stats()
+    }
+
+    /// Remove all cached priorities.
+    pub fn clear(&self) {
+        self.priorities.clear();
+    }
+
+    /// Get the wrapped prioritizator.
+    pub fn into_inner(self) -> PP {
+        self.prioritizator
+    }
+}
+
+impl<M, PP> PathPrioritizator<M> for CachedPathPrioritizator<PP>
+where
+    M: GrowthMetrics,
+    PP: PathPrioritizator<M>,
+{
+    fn prioritize(&self, factors: PathPrioritizationFactors<M>) -> Option<f64> {
+        self.priorities.get_or_insert_with(self.key(&factors), || {
+            self.prioritizator.prioritize(factors)
+        })
+    }
+
+    fn prioritize_many(&self, factors: &[PathPrioritizationFactors<M>]) -> Vec<Option<f64>> {
+        let keys = factors.iter().map(|factors| self.key(factors)).collect();
+        self.priorities.get_or_insert_many(keys, |missing| {
+            let factors = missing
+                .iter()
+                .map(|&index| factors[index])
+                .collect::<Vec<_>>();
+            self.prioritizator.prioritize_many(&factors)
+        })
+    }
+}
+
+impl<PP> CachedPathPrioritizator<PP> {
+    fn key<M>(&self, factors: &PathPrioritizationFactors<M>) -> PriorityKey
+    where
+        M: GrowthMetrics,
+    {
+        let context = factors.context;
+        PriorityKey {
+            node_id: context.node_id,
+            heading: context.heading.radian().to_bits() as i64,
+            degree: context.degree,
+            nearest_path_distance: context
+                .nearest_path_distance
+                .map(|distance| self.quantizer.value(distance)),
+            origin: context.origin,
+            site_start: self.quantizer.site(&factors.site_start),
+            site_end: self.quantizer.site(&factors.site_end),
+            path_length: factors.path_length.to_bits() as i64,
+            stage: factors.stage.as_num(),
+            creates_bridge: factors.creates_bridge,
+            road_density: factors.road_density.map(|density| density.to_bits() as i64),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use crate::{
+        core::{container::path_network::PathNetwork, geometry::angle::Angle},
+        transport::{
+            context::GrowthContext,
+            node::TransportNode,
+            params::{metrics::PathMetrics, numeric::Stage},
+        },
+    };
+
+    use super::*;
+
+    struct CountingTerrain {
+        calls: Cell<usize>,
+    }
+
+    impl TerrainProvider for CountingTerrain {
+        fn get_elevation(&self, site: &Site) -> Option<f64> {
+            self.calls.set(self.calls.get() + 1);
+            Some(site.x + site.y)
+        }
+    }
+
+    #[test]
+    fn test_cached_terrain_provider() {
+        let provider = CachedTerrainProvider::new(CountingTerrain {
+            calls: Cell::new(0),
+        })
+        .capacity(2);
+        assert_eq!(provider.get_elevation(&Site::new(1.0, 2.0)), Some(3.0));
+        assert_eq!(provider.get_elevation(&Site::new(1.0, 2.0)), Some(3.0));
+        assert_eq!(provider.get_elevation(&Site::new(2.0, 2.0)), Some(4.0));
+        assert_eq!(provider.get_elevation(&Site::new(3.0, 2.0)), Some(5.0));
+        // (1.0, 2.0) was evicted.
+        assert_eq!(provider.get_elevation(&Site::new(1.0, 2.0)), Some(3.0));
+        assert_eq!(provider.stats(), CacheStats { hits: 1, misses: 4 });
+
+        let provider = provider.into_inner();
+        assert_eq!(provider.calls.get(), 4);
+
+        // quantized sites share the elevation.
+        let provider = CachedTerrainProvider::new(provider).resolution(0.5);
+        assert_eq!(provider.get_elevation(&Site::new(1.0, 2.0)), Some(3.0));
+        assert_eq!(provider.get_elevation(&Site::new(1.1, 2.1)), Some(3.0));
+        assert_eq!(provider.stats(), CacheStats { hits: 1, misses: 1 });
+
+        // only the missing sites are passed to the provider, and duplicated sites are computed once.
+        let sites = [
+            Site::new(1.0, 2.0),
+            Site::new(0.0, 0.0),
+            Site::new(0.0, 0.0),
+        ];
+        assert_eq!(
+            provider.get_elevations(&sites),
+            vec![Some(3.0), Some(0.0), Some(0.0)]
+        );
+        assert_eq!(provider.stats(), CacheStats { hits: 3, misses: 2 });
+        assert_eq!(provider.into_inner().calls.get(), 6);
+    }
+
+    /// Terrain which doesn't return one elevation per site in batches.
+    struct ShortTerrain;
+
+    impl TerrainProvider for ShortTerrain {
+        fn get_elevation(&self, _: &Site) -> Option<f64> {
+            Some(0.0)
+        }
+
+        fn get_elevations(&self, _: &[Site]) -> Vec<Option<f64>> {
+            vec![]
+        }
+    }
+
+    #[test]
+    fn test_cached_terrain_provider_with_short_batch() {
+        let provider = CachedTerrainProvider::new(ShortTerrain);
+        assert_eq!(provider.get_elevation(&Site::new(0.0, 0.0)), Some(0.0));
+
+        let sites = [Site::new(0.0, 0.0), Site::new(1.0, 0.0)];
+        assert_eq!(provider.get_elevations(&sites), vec![Some(0.0), None]);
+        // the missing elevation is not cached.
+        assert_eq!(provider.get_elevation(&Site::new(1.0, 0.0)), Some(0.0));
+        assert_eq!(provider.stats(), CacheStats { hits: 1, misses: 3 });
+    }
+
+    struct CountingPrioritizator {
+        calls: Cell<usize>,
+    }
+
+    impl PathPrioritizator for CountingPrioritizator {
+        fn prioritize(&self, factors: PathPrioritizationFactors) -> Option<f64> {
+            self.calls.set(self.calls.get() + 1);
+            Some(factors.path_length + factors.context.degree as f64)
+        }
+    }
+
+    #[test]
+    fn test_cached_path_prioritizator() {
+        let prioritizator = CachedPathPrioritizator::new(CountingPrioritizator {
+            calls: Cell::new(0),
+        });
+
+        let node = TransportNode::default();
+        let network = PathNetwork::new();
+        let metrics = PathMetrics::default();
+        let context = |node_id: usize, degree: usize| GrowthContext {
+            node_id: NodeId::new(node_id),
+            node: &node,
+            heading: Angle::new(0.0),
+            stage: Stage::default(),
+            metrics: &metrics,
+            degree,
+            nearest_path_distance: None,
+            origin: None,
+            road_density: None,
+            network: &network,
+        };
+        let factors = |context, path_length| PathPrioritizationFactors {
+            site_start: Site::new(0.0, 0.0),
+            site_end: Site::new(1.0, 0.0),
+            path_length,
+            stage: Stage::default(),
+            creates_bridge: false,
+            road_density: None,
+            context,
+        };
+
+        let (context0, context1) = (context(0, 0), context(1, 0));
+        assert_eq!(prioritizator.prioritize(factors(&context0, 1.0)), Some(1.0));
+        assert_eq!(prioritizator.prioritize(factors(&context0, 1.0)), Some(1.0));
+        // the factors or the node id differ.
+        assert_eq!(prioritizator.prioritize(factors(&context0, 2.0)), Some(2.0));
+        assert_eq!(prioritizator.prioritize(factors(&context1, 1.0)), Some(1.0));
+        assert_eq!(prioritizator.stats(), CacheStats { hits: 1, misses: 3 });
+
+        // the degree of the node is a part of the key.
+        let context0 = context(0, 5);
+        assert_eq!(prioritizator.prioritize(factors(&context0, 1.0)), Some(6.0));
+        assert_eq!(
+            prioritizator.prioritize_many(&[factors(&context0, 1.0), factors(&context0, 3.0)]),
+            vec![Some(6.0), Some(8.0)]
+        );
+        assert_eq!(prioritizator.stats(), CacheStats { hits: 2, misses: 5 });
+        assert_eq!(prioritizator.into_inner().calls.get(), 5);
+    }
+}
@@ -0,0 +1,536 @@
+use std::collections::BTreeMap;
+
+use crate::core::geometry::{polygon::Polygon, site::Site};
+
+use super::{
+    context::GrowthContext,
+    params::{
+        metrics::{GrowthMetrics, PathMetrics},
+        numeric::Stage,
+        priority::PathPrioritizationFactors,
+        rules::{
+            isoline::IsolineTarget,
+            slope::{SlopeLimit, SlopePath},
+            ElevationDiffLimit, TransportRules,
+        },
+    },
+    traits::{PathPrioritizator, RandomF64Provider, TerrainProvider, TransportRulesProvider},
+};
+
+type BoxedRulesProvider<'a, M> = Box<dyn TransportRulesProvider<M> + 'a>;
+
+/// `TransportRulesProvider` defined by a closure.
+pub struct FnRulesProvider<F>(pub F);
+
+impl<M, F> TransportRulesProvider<M> for FnRulesProvider<F>
+where
+    M: GrowthMetrics,
+    F: Fn(&GrowthContext<M>) -> Option<TransportRules>,
+{
+    fn get_rules(&self, context: &GrowthContext<M>) -> Option<TransportRules> {
+        (self.0)(context)
+    }
+}
+
+/// `TerrainProvider` defined by a closure.
+pub struct FnTerrainProvider<F>(pub F);
+
+impl<F> TerrainProvider for FnTerrainProvider<F>
+where
+    F: Fn(&Site) -> Option<f64>,
+{
+    fn get_elevation(&self, site: &Site) -> Option<f64> {
+        (self.0)(site)
+    }
+}
+
+/// `PathPrioritizator` defined by a closure.
+pub struct FnPathPrioritizator<F>(pub F);
+
+impl<M, F> PathPrioritizator<M> for FnPathPrioritizator<F>
+where
+    M: GrowthMetrics,
+    F: Fn(PathPrioritizationFactors<M>) -> Option<f64>,
+{
+    fn prioritize(&self, factors: PathPrioritizationFactors<M>) -> Option<f64> {
+        (self.0)(factors)
+    }
+}
+
+/// `RandomF64Provider` defined by a closure.
+pub struct FnRandomF64Provider<F>(pub F);
+
+impl<F> RandomF64Provider for FnRandomF64Provider<F>
+where
+    F: FnMut() -> f64,
+{
+    fn gen_f64(&mut self) -> f64 {
+        (self.0)()
+    }
+}
+
+/// Rules provider switched by the region where the path is extended from.
+///
+/// The first region containing the site of the node is used.
+/// If no region contains the site, the default provider is used if set.
+pub struct RegionRulesProvider<'a, M = PathMetrics>
+where
+    M: GrowthMetrics,
+{
+    regions: Vec<(Polygon, BoxedRulesProvider<'a, M>)>,
+    default: Option<BoxedRulesProvider<'a, M>>,
+}
+
+impl<M> Default for RegionRulesProvider<'_, M>
+where
+    M: GrowthMetrics,
+{
+    fn default() -> Self {
+        Self {
+            regions: Vec::new(),
+            default: None,
+        }
+    }
+}
+
+impl<'a, M> RegionRulesProvider<'a, M>
+where
+    M: GrowthMetrics,
+{
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a region with the provider.
+    pub fn region<P>(mut self, region: Polygon, provider: P) -> Self
+    where
+        P: TransportRulesProvider<M> + 'a,
+    {
+        self.regions.push((region, Box::new(provider)));
+        self
+    }
+
+    /// Set the provider used outside of all regions.
+    pub fn default_provider<P>(mut self, provider: P) -> Self
+    where
+        P: TransportRulesProvider<M> + 'a,
+    {
+        self.default = Some(Box::new(provider));
+        self
+    }
+}
+
+impl<M> TransportRulesProvider<M> for RegionRulesProvider<'_, M>
+where
+    M: GrowthMetrics,
+{
+    fn get_rules(&self, context: &GrowthContext<M>) -> Option<TransportRules> {
+        let site = &context.node.site;
+        self.regions
+            .iter()
+            .find(|(region, _)| region.contains(site))
+            .map(|(_, provider)| provider)
+            .or(self.default.as_ref())?
+            .get_rules(context)
+    }
+}
+
+/// Rules provider dispatched by the stage of the path.
+///
+/// The provider of the largest stage not exceeding the stage of the path is used.
+pub struct StageRulesProvider<'a, M = PathMetrics>
+where
+    M: GrowthMetrics,
+{
+    stages: BTreeMap<Stage, BoxedRulesProvider<'a, M>>,
+}
+
+impl<M> Default for StageRulesProvider<'_, M>
+where
+    M: GrowthMetrics,
+{
+    fn default() -> Self {
+        Self {
+            stages: BTreeMap::new(),
+        }
+    }
+}
+
+impl<'a, M> StageRulesProvider<'a, M>
+where
+    M: GrowthMetrics,
+{
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the provider for the paths from the stage.
+    pub fn stage<P>(mut self, stage: Stage, provider: P) -> Self
+    where
+        P: TransportRulesProvider<M> + 'a,
+    {
+        self.stages.insert(stage, Box::new(provider));
+        self
+    }
+}
+
+impl<M> TransportRulesProvider<M> for StageRulesProvider<'_, M>
+where
+    M: GrowthMetrics,
+{
+    fn get_rules(&self, context: &GrowthContext<M>) -> Option<TransportRules> {
+        let (_, provider) = self.stages.range(..=context.stage).next_back()?;
+        provider.get_rules(context)
+    }
+}
+
+/// Rules provider which tries the providers in order until one returns rules.
+pub struct FallbackRulesProvider<'a, M = PathMetrics>
+where
+    M: GrowthMetrics,
+{
+    providers: Vec<BoxedRulesProvider<'a, M>>,
+}
+
+impl<M> Default for FallbackRulesProvider<'_, M>
+where
+    M: GrowthMetrics,
+{
+    fn default() -> Self {
+        Self {
+            providers: Vec::new(),
+        }
+    }
+}
+
+impl<'a, M> FallbackRulesProvider<'a, M>
+where
+    M: GrowthMetrics,
+{
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a provider tried after the providers added before.
+    pub fn or<P>(mut self, provider: P) -> Self
+    where
+        P: TransportRulesProvider<M> + 'a,
+    {
+        self.providers.push(Box::new(provider));
+        self
+    }
+}
+
+impl<M> TransportRulesProvider<M> for FallbackRulesProvider<'_, M>
+where
+    M: GrowthMetrics,
+{
+    fn get_rules(&self, context: &GrowthContext<M>) -> Option<TransportRules> {
+        self.providers
+            .iter()
+            .find_map(|provider| provider.get_rules(context))
+    }
+}
+
+/// Rules provider which blends the rules of two providers linearly.
+///
+/// The weight (clamped to `[0, 1]`) is `0` for the rules of `from`, and `1` for the rules of `to`.
+/// Numeric fields are interpolated (integers are rounded), and the slope limit is interpolated
+/// by the maximum elevation difference. The iso-line target is taken from the dominant rules.
+/// If either provider returns `None`, the rules of the other are used as they are.
+pub struct BlendRulesProvider<'a, W, M = PathMetrics>
+where
+    W: Fn(&GrowthContext<M>) -> f64,
+    M: GrowthMetrics,
+{
+    from: BoxedRulesProvider<'a, M>,
+    to: BoxedRulesProvider<'a, M>,
+    weight: W,
+}
+
+impl<'a, W, M> BlendRulesProvider<'a, W, M>
+where
+    W: Fn(&GrowthContext<M>) -> f64,
+    M: GrowthMetrics,
+{
+    pub fn new<P0, P1>(from: P0, to: P1, weight: W) -> Self
+    where
+        P0: TransportRulesProvider<M> + 'a,
+        P1: TransportRulesProvider<M> + 'a,
+    {
+        Self {
+            from: Box::new(from),
+            to: Box::new(to),
+            weight,
+        }
+    }
+}
+
+impl<W, M> TransportRulesProvider<M> for BlendRulesProvider<'_, W, M>
+where
+    W: Fn(&GrowthContext<M>) -> f64,
+    M: GrowthMetrics,
+{
+    fn get_rules(&self, context: &GrowthContext<M>) -> Option<TransportRules> {
+        match (self.from.get_rules(context), self.to.get_rules(context)) {
+            (Some(from), Some(to)) => Some(blend_rules(
+                &from,
+                &to,
+                (self.weight)(context).clamp(0.0, 1.0),
+            )),
+            (from, to) => from.or(to),
+        }
+    }
+}
+
+/// Interpolate the rules by the weight `t` in `[0, 1]`.
+fn blend_rules(from: &TransportRules, to: &TransportRules, t: f64) -> TransportRules {
+    let lerp = |a: f64, b: f64| a + (b - a) * t;
+    let lerp_count = |a: usize, b: usize| lerp(a as f64, b as f64).round() as usize;
+    let dominant = if t < 0.5 { from } else { to };
+
+    let mut rules = dominant.clone();
+    rules.path_normal_length = lerp(from.path_normal_length, to.path_normal_length);
+    rules.path_extra_length_for_intersection = lerp(
+        from.path_extra_length_for_intersection,
+        to.path_extra_length_for_intersection,
+    );
+    rules.path_grade_separation_elevation_diff_threshold = lerp(
+        from.path_grade_separation_elevation_diff_threshold,
+        to.path_grade_separation_elevation_diff_threshold,
+    );
+    rules.path_slope_elevation_diff_limit = blend_slope_limits(
+        &from.path_slope_elevation_diff_limit,
+        &to.path_slope_elevation_diff_limit,
+        t,
+    );
+
+    let (branch, branch_from, branch_to) = (
+        &mut rules.branch_rules,
+        &from.branch_rules,
+        &to.branch_rules,
+    );
+    branch.branch_density = lerp(branch_from.branch_density, branch_to.branch_density);
+    branch.staging_probability = lerp(
+        branch_from.staging_probability,
+        branch_to.staging_probability,
+    );
+
+    let (direction, direction_from, direction_to) = (
+        &mut rules.path_direction_rules,
+        &from.path_direction_rules,
+        &to.path_direction_rules,
+    );
+    direction.max_radian = lerp(direction_from.max_radian, direction_to.max_radian);
+    direction.comparison_step =
+        lerp_count(direction_from.comparison_step, direction_to.comparison_step);
+    direction.contour_affinity = lerp(
+        direction_from.contour_affinity,
+        direction_to.contour_affinity,
+    );
+
+    let (bridge, bridge_from, bridge_to) = (
+        &mut rules.bridge_rules,
+        &from.bridge_rules,
+        &to.bridge_rules,
+    );
+    bridge.max_bridge_length = lerp(bridge_from.max_bridge_length, bridge_to.max_bridge_length);
+    bridge.check_step = lerp_count(bridge_from.check_step, bridge_to.check_step);
+
+    let (switchback, switchback_from, switchback_to) = (
+        &mut rules.switchback_rules,
+        &from.switchback_rules,
+        &to.switchback_rules,
+    );
+    switchback.max_hairpins = lerp_count(switchback_from.max_hairpins, switchback_to.max_hairpins);
+    switchback.min_hairpin_spacing = lerp(
+        switchback_from.min_hairpin_spacing,
+        switchback_to.min_hairpin_spacing,
+    );
+    switchback.check_step = lerp_count(switchback_from.check_step, switchback_to.check_step);
+
+    let (isoline, isoline_from, isoline_to) = (
+        &mut rules.isoline_rules,
+        &from.isoline_rules,
+        &to.isoline_rules,
+    );
+    if let (Some(IsolineTarget::Elevation(a)), Some(IsolineTarget::Elevation(b))) =
+        (isoline_from.target, isoline_to.target)
+    {
+        isoline.target = Some(IsolineTarget::Elevation(lerp(a, b)));
+    }
+    isoline.offset = lerp(isoline_from.offset, isoline_to.offset);
+    isoline.check_step = lerp_count(isoline_from.check_step, isoline_to.check_step);
+
+    let (junction, junction_from, junction_to) = (
+        &mut rules.junction_rules,
+        &from.junction_rules,
+        &to.junction_rules,
+    );
+    junction.min_angle = lerp(junction_from.min_angle, junction_to.min_angle);
+    junction.min_intersection_spacing = lerp(
+        junction_from.min_intersection_spacing,
+        junction_to.min_intersection_spacing,
+    );
+
+    let (clearance, clearance_from, clearance_to) = (
+        &mut rules.clearance_rules,
+        &from.clearance_rules,
+        &to.clearance_rules,
+    );
+    clearance.min_parallel_clearance = lerp(
+        clearance_from.min_parallel_clearance,
+        clearance_to.min_parallel_clearance,
+    );
+    clearance.parallel_max_radian = lerp(
+        clearance_from.parallel_max_radian,
+        clearance_to.parallel_max_radian,
+    );
+
+    rules
+}
+
+/// Interpolate the maximum elevation differences of the slope limits.
+fn blend_slope_limits(
+    from: &ElevationDiffLimit,
+    to: &ElevationDiffLimit,
+    t: f64,
+) -> ElevationDiffLimit {
+    if t == 0.0 || from == to {
+        return from.clone();
+    }
+    if t == 1.0 {
+        return to.clone();
+    }
+    let (from, to) = (from.clone(), to.clone());
+    ElevationDiffLimit::from_fn(move |path: &SlopePath| {
+        let (a, b) = (from.max_elevation_diff(path), to.max_elevation_diff(path));
+        // the interpolation of an infinite limit is the limit itself.
+        if a.is_infinite() || b.is_infinite() {
+            if t < 0.5 {
+                a
+            } else {
+                b
+            }
+        } else {
+            a + (b - a) * t
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        core::{
+            container::path_network::{NodeId, PathNetwork},
+            geometry::angle::Angle,
+        },
+        transport::{node::TransportNode, params::rules::bridge::BridgeRules},
+    };
+
+    use super::*;
+
+    fn rules_with_length(
+        length: f64,
+    ) -> FnRulesProvider<impl Fn(&GrowthContext) -> Option<TransportRules>> {
+        FnRulesProvider(move |_: &GrowthContext| {
+            Some(TransportRules::default().path_normal_length(length))
+        })
+    }
+
+    fn get_length<P>(provider: &P, site: Site, stage: usize) -> Option<f64>
+    where
+        P: TransportRulesProvider,
+    {
+        let network = PathNetwork::new();
+        let node = TransportNode::new(site, 0.0, Stage::from_num(stage), false);
+        let metrics = PathMetrics::default();
+        let context = GrowthContext {
+            node_id: NodeId::new(0),
+            node: &node,
+            heading: Angle::new(0.0),
+            stage: Stage::from_num(stage),
+            metrics: &metrics,
+            degree: 0,
+            nearest_path_distance: None,
+            origin: None,
+            road_density: None,
+            network: &network,
+        };
+        provider
+            .get_rules(&context)
+            .map(|rules| rules.path_normal_length)
+    }
+
+    #[test]
+    fn test_region_and_stage_providers() {
+        let square = Polygon::new(vec![
+            Site::new(0.0, 0.0),
+            Site::new(1.0, 0.0),
+            Site::new(1.0, 1.0),
+            Site::new(0.0, 1.0),
+        ]);
+        let region = RegionRulesProvider::new().region(square.clone(), rules_with_length(1.0));
+        assert_eq!(get_length(&region, Site::new(0.5, 0.5), 0), Some(1.0));
+        assert_eq!(get_length(&region, Site::new(2.0, 0.5), 0), None);
+        let region = region.default_provider(rules_with_length(2.0));
+        assert_eq!(get_length(&region, Site::new(2.0, 0.5), 0), Some(2.0));
+
+        let stage = StageRulesProvider::new()
+            .stage(Stage::from_num(1), rules_with_length(1.0))
+            .stage(Stage::from_num(3), rules_with_length(3.0));
+        assert_eq!(get_length(&stage, Site::new(0.0, 0.0), 0), None);
+        assert_eq!(get_length(&stage, Site::new(0.0, 0.0), 2), Some(1.0));
+        assert_eq!(get_length(&stage, Site::new(0.0, 0.0), 5), Some(3.0));
+    }
+
+    #[test]
+    fn test_fallback_and_blend_providers() {
+        let fallback = FallbackRulesProvider::new()
+            .or(FnRulesProvider(|context: &GrowthContext| {
+                (context.stage.as_num() == 0).then(TransportRules::default)
+            }))
+            .or(rules_with_length(2.0));
+        assert_eq!(get_length(&fallback, Site::new(0.0, 0.0), 0), Some(0.0));
+        assert_eq!(get_length(&fallback, Site::new(0.0, 0.0), 1), Some(2.0));
+
+        let rules_from = TransportRules::default()
+            .path_normal_length(1.0)
+            .path_slope_elevation_diff_limit(ElevationDiffLimit::Linear(1.0))
+            .bridge_rules(BridgeRules {
+                max_bridge_length: 0.0,
+                check_step: 1,
+            });
+        let rules_to = TransportRules::default()
+            .path_normal_length(3.0)
+            .path_slope_elevation_diff_limit(ElevationDiffLimit::Linear(3.0))
+            .bridge_rules(BridgeRules {
+                max_bridge_length: 4.0,
+                check_step: 4,
+            });
+
+        let (from, to) = (rules_from.clone(), rules_to.clone());
+        let blend = BlendRulesProvider::new(
+            FnRulesProvider(move |_: &GrowthContext| Some(from.clone())),
+            FnRulesProvider(move |_: &GrowthContext| Some(to.clone())),
+            |context: &GrowthContext| context.node.site.x,
+        );
+        assert_eq!(get_length(&blend, Site::new(0.5, 0.0), 0), Some(2.0));
+        assert_eq!(get_length(&blend, Site::new(-1.0, 0.0), 0), Some(1.0));
+        assert_eq!(get_length(&blend, Site::new(2.0, 0.0), 0), Some(3.0));
+
+        let rules = blend_rules(&rules_from, &rules_to, 0.5);
+        assert_eq!(rules.bridge_rules.max_bridge_length, 2.0);
+        assert_eq!(rules.bridge_rules.check_step, 3);
+        let path = SlopePath::new(
+            TransportNode::new(Site::new(0.0, 0.0), 0.0, Stage::from_num(0), false),
+            TransportNode::new(Site::new(1.0, 0.0), 2.0, Stage::from_num(0), false),
+        );
+        assert_eq!(
+            rules
+                .path_slope_elevation_diff_limit
+                .max_elevation_diff(&path),
+            2.0
+        );
+    }
+}
@@ -0,0 +1,549 @@
+use std::collections::BTreeMap;
+
+/// Error in parsing an expression.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExpressionError {
+    /// The byte position in the source where the error is found.
+    pub position: usize,
+    pub message: String,
+}
+
+impl std::fmt::Display for ExpressionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} at position {}", self.message, self.position)
+    }
+}
+
+impl std::error::Error for ExpressionError {}
+
+/// Values of the variables referred by expressions.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Variables(BTreeMap<String, f64>);
+
+impl Variables {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the value of the variable.
+    pub fn set(&mut self, name: &str, value: f64) {
+        self.0.insert(name.to_string(), value);
+    }
+
+    pub fn get(&self, name: &str) -> Option<f64> {
+        self.0.get(name).copied()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum UnaryOp {
+    Neg,
+    Not,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum BinaryOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Rem,
+    Pow,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Eq,
+    Ne,
+    And,
+    Or,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Function {
+    Abs,
+    Sqrt,
+    Exp,
+    Ln,
+    Floor,
+    Ceil,
+    Round,
+    Sin,
+    Cos,
+    Min,
+    Max,
+    Pow,
+    Clamp,
+    If,
+}
+
+impl Function {
+    fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "abs" => Function::Abs,
+            "sqrt" => Function::Sqrt,
+            "exp" => Function::Exp,
+            "ln" => Function::Ln,
+            "floor" => Function::Floor,
+            "ceil" => Function::Ceil,
+            "round" => Function::Round,
+            "sin" => Function::Sin,
+            "cos" => Function::Cos,
+            "min" => Function::Min,
+            "max" => Function::Max,
+            "pow" => Function::Pow,
+            "clamp" => Function::Clamp,
+            "if" => Function::If,
+            _ => return None,
+        })
+    }
+
+    /// Check if the function accepts the number of arguments.
+    fn accepts(&self, arg_count: usize) -> bool {
+        match self {
+            Function::Min | Function::Max => arg_count >= 1,
+            Function::Pow => arg_count == 2,
+            Function::Clamp | Function::If => arg_count == 3,
+            _ => arg_count == 1,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Node {
+    Number(f64),
+    Variable(String),
+    Unary(UnaryOp, Box<Node>),
+    Binary(BinaryOp, Box<Node>, Box<Node>),
+    Call(Function, Vec<Node>),
+}
+
+fn from_bool(value: bool) -> f64 {
+    if value {
+        1.0
+    } else {
+        0.0
+    }
+}
+
+impl Node {
+    fn evaluate(&self, variables: &Variables) -> Option<f64> {
+        match self {
+            Node::Number(value) => Some(*value),
+            Node::Variable(name) => variables.get(name),
+            Node::Unary(op, node) => {
+                let value = node.evaluate(variables)?;
+                Some(match op {
+                    UnaryOp::Neg => -value,
+                    UnaryOp::Not => from_bool(value == 0.0),
+                })
+            }
+            Node::Binary(BinaryOp::And, lhs, rhs) => {
+                if lhs.evaluate(variables)? == 0.0 {
+                    return Some(0.0);
+                }
+                Some(from_bool(rhs.evaluate(variables)? != 0.0))
+            }
+            Node::Binary(BinaryOp::Or, lhs, rhs) => {
+                if lhs.evaluate(variables)? != 0.0 {
+                    return Some(1.0);
+                }
+                Some(from_bool(rhs.evaluate(variables)? != 0.0))
+            }
+            Node::Binary(op, lhs, rhs) => {
+                let (lhs, rhs) = (lhs.evaluate(variables)?, rhs.evaluate(variables)?);
+                Some(match op {
+                    BinaryOp::Add => lhs + rhs,
+                    BinaryOp::Sub => lhs - rhs,
+                    BinaryOp::Mul => lhs * rhs,
+                    BinaryOp::Div => lhs / rhs,
+                    BinaryOp::Rem => lhs.rem_euclid(rhs),
+                    BinaryOp::Pow => lhs.powf(rhs),
+                    BinaryOp::Lt => from_bool(lhs < rhs),
+                    BinaryOp::Le => from_bool(lhs <= rhs),
+                    BinaryOp::Gt => from_bool(lhs > rhs),
+                    BinaryOp::Ge => from_bool(lhs >= rhs),
+                    BinaryOp::Eq => from_bool(lhs == rhs),
+                    BinaryOp::Ne => from_bool(lhs != rhs),
+                    BinaryOp::And | BinaryOp::Or => unreachable!(),
+                })
+            }
+            Node::Call(Function::If, args) => {
+                // only the selected branch is evaluated.
+                if args[0].evaluate(variables)? != 0.0 {
+                    args[1].evaluate(variables)
+                } else {
+                    args[2].evaluate(variables)
+                }
+            }
+            Node::Call(function, args) => {
+                let args = args
+                    .iter()
+                    .map(|arg| arg.evaluate(variables))
+                    .collect::<Option<Vec<_>>>()?;
+                Some(match function {
+                    Function::Abs => args[0].abs(),
+                    Function::Sqrt => args[0].sqrt(),
+                    Function::Exp => args[0].exp(),
+                    Function::Ln => args[0].ln(),
+                    Function::Floor => args[0].floor(),
+                    Function::Ceil => args[0].ceil(),
+                    Function::Round => args[0].round(),
+                    Function::Sin => args[0].sin(),
+                    Function::Cos => args[0].cos(),
+                    Function::Min => args.into_iter().fold(f64::INFINITY, f64::min),
+                    Function::Max => args.into_iter().fold(f64::NEG_INFINITY, f64::max),
+                    Function::Pow => args[0].powf(args[1]),
+                    Function::Clamp => {
+                        // `f64::clamp` panics if the bounds are inverted or NaN.
+                        let (min, max) = (args[1], args[2]);
+                        if min.is_nan() || max.is_nan() || min > max {
+                            return None;
+                        }
+                        args[0].clamp(min, max)
+                    }
+                    Function::If => unreachable!(),
+                })
+            }
+        }
+    }
+
+    fn collect_variables<'a>(&'a self, names: &mut Vec<&'a str>) {
+        match self {
+            Node::Number(_) => {}
+            Node::Variable(name) => names.push(name),
+            Node::Unary(_, node) => node.collect_variables(names),
+            Node::Binary(_, lhs, rhs) => {
+                lhs.collect_variables(names);
+                rhs.collect_variables(names);
+            }
+            Node::Call(_, args) => args.iter().for_each(|arg| arg.collect_variables(names)),
+        }
+    }
+}
+
+/// Arithmetic expression over named variables.
+///
+/// Supported syntax:
+///  - numbers, variables (`population_density`, `metrics.branch_count`) and `pi`.
+///  - operators `+ - * / % ^`, comparisons `< <= > >= == !=` and logical `&& || !`,
+///    where `true` is `1` and `false` is `0`.
+///  - functions `abs sqrt exp ln floor ceil round sin cos min max pow clamp if`,
+///    where `if(condition, then, else)` evaluates only the selected branch.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Expression {
+    source: String,
+    node: Node,
+}
+
+impl Expression {
+    /// Parse an expression.
+    pub fn parse(source: &str) -> Result<Self, ExpressionError> {
+        let mut parser = Parser {
+            source,
+            position: 0,
+        };
+        let node = parser.parse_or()?;
+        parser.skip_whitespace();
+        if parser.position < source.len() {
+            return Err(parser.error("unexpected token"));
+        }
+        Ok(Self {
+            source: source.to_string(),
+            node,
+        })
+    }
+
+    /// Create an expression of a constant value.
+    pub fn constant(value: f64) -> Self {
+        Self {
+            source: value.to_string(),
+            node: Node::Number(value),
+        }
+    }
+
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// Evaluate the expression.
+    ///
+    /// If any variable is not defined or the bounds of `clamp` are inverted or NaN, `None` is returned.
+    pub fn evaluate(&self, variables: &Variables) -> Option<f64> {
+        self.node.evaluate(variables)
+    }
+
+    /// Get the names of the variables referred by the expression.
+    pub fn variables(&self) -> Vec<&str> {
+        let mut names = Vec::new();
+        self.node.collect_variables(&mut names);
+        names.sort();
+        names.dedup();
+        names
+    }
+}
+
+/// Recursive descent parser of expressions.
+struct Parser<'a> {
+    source: &'a str,
+    position: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn error(&self, message: &str) -> ExpressionError {
+        ExpressionError {
+            position: self.position,
+            message: message.to_string(),
+        }
+    }
+
+    fn rest(&self) -> &'a str {
+        let source: &'a str = self.source;
+        &source[self.position..]
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = self.rest();
+        self.position += rest.len() - rest.trim_start().len();
+    }
+
+    /// Consume the token if it comes next.
+    fn eat(&mut self, token: &str) -> bool {
+        self.skip_whitespace();
+        if self.rest().starts_with(token) {
+            self.position += token.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Parse binary operators of the same precedence (left associative).
+    fn parse_binary(
+        &mut self,
+        operators: &[(&str, BinaryOp)],
+        parse_operand: fn(&mut Self) -> Result<Node, ExpressionError>,
+    ) -> Result<Node, ExpressionError> {
+        let mut node = parse_operand(self)?;
+        'outer: loop {
+            for (token, op) in operators {
+                if self.eat(token) {
+                    let rhs = parse_operand(self)?;
+                    node = Node::Binary(*op, Box::new(node), Box::new(rhs));
+                    continue 'outer;
+                }
+            }
+            return Ok(node);
+        }
+    }
+
+    fn parse_or(&mut self) -> Result<Node, ExpressionError> {
+        self.parse_binary(&[("||", BinaryOp::Or)], Self::parse_and)
+    }
+
+    fn parse_and(&mut self) -> Result<Node, ExpressionError> {
+        self.parse_binary(&[("&&", BinaryOp::And)], Self::parse_comparison)
+    }
+
+    fn parse_comparison(&mut self) -> Result<Node, ExpressionError> {
+        // longer tokens are checked first.
+        self.parse_binary(
+            &[
+                ("<=", BinaryOp::Le),
+                (">=", BinaryOp::Ge),
+                ("==", BinaryOp::Eq),
+                ("!=", BinaryOp::Ne),
+                ("<", BinaryOp::Lt),
+                (">", BinaryOp::Gt),
+            ],
+            Self::parse_additive,
+        )
+    }
+
+    fn parse_additive(&mut self) -> Result<Node, ExpressionError> {
+        self.parse_binary(
+            &[("+", BinaryOp::Add), ("-", BinaryOp::Sub)],
+            Self::parse_multiplicative,
+        )
+    }
+
+    fn parse_multiplicative(&mut self) -> Result<Node, ExpressionError> {
+        self.parse_binary(
+            &[
+                ("*", BinaryOp::Mul),
+                ("/", BinaryOp::Div),
+                ("%", BinaryOp::Rem),
+            ],
+            Self::parse_unary,
+        )
+    }
+
+    fn parse_unary(&mut self) -> Result<Node, ExpressionError> {
+        if self.eat("-") {
+            return Ok(Node::Unary(UnaryOp::Neg, Box::new(self.parse_unary()?)));
+        }
+        // `!` is not confused with `!=` because binary operators don't come here.
+        if self.eat("!") {
+            return Ok(Node::Unary(UnaryOp::Not, Box::new(self.parse_unary()?)));
+        }
+        self.parse_power()
+    }
+
+    fn parse_power(&mut self) -> Result<Node, ExpressionError> {
+        let base = self.parse_primary()?;
+        if self.eat("^") {
+            // right associative, and binds tighter than unary minus on the left.
+            let exponent = self.parse_unary()?;
+            return Ok(Node::Binary(
+                BinaryOp::Pow,
+                Box::new(base),
+                Box::new(exponent),
+            ));
+        }
+        Ok(base)
+    }
+
+    fn parse_primary(&mut self) -> Result<Node, ExpressionError> {
+        self.skip_whitespace();
+        if self.eat("(") {
+            let node = self.parse_or()?;
+            if !self.eat(")") {
+                return Err(self.error("expected `)`"));
+            }
+            return Ok(node);
+        }
+
+        let rest = self.rest();
+        let first = rest
+            .chars()
+            .next()
+            .ok_or_else(|| self.error("unexpected end"))?;
+        if first.is_ascii_digit() || first == '.' {
+            let length = rest
+                .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+                .unwrap_or(rest.len());
+            // exponent notation such as `1e-3`.
+            let length = match rest[length..].strip_prefix(['e', 'E']) {
+                Some(exponent) => {
+                    let sign = usize::from(exponent.starts_with(['+', '-']));
+                    let digits = exponent[sign..]
+                        .find(|c: char| !c.is_ascii_digit())
+                        .unwrap_or(exponent.len() - sign);
+                    if digits > 0 {
+                        length + 1 + sign + digits
+                    } else {
+                        length
+                    }
+                }
+                None => length,
+            };
+            let value = rest[..length]
+                .parse::<f64>()
+                .map_err(|_| self.error("invalid number"))?;
+            self.position += length;
+            return Ok(Node::Number(value));
+        }
+
+        if first.is_ascii_alphabetic() || first == '_' {
+            let length = rest
+                .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_' || c == '.'))
+                .unwrap_or(rest.len());
+            let name = &rest[..length];
+            let start = self.position;
+            self.position += length;
+
+            if self.eat("(") {
+                let function = Function::from_name(name).ok_or_else(|| ExpressionError {
+                    position: start,
+                    message: format!("unknown function `{}`", name),
+                })?;
+                let mut args = Vec::new();
+                if !self.eat(")") {
+                    loop {
+                        args.push(self.parse_or()?);
+                        if self.eat(")") {
+                            break;
+                        }
+                        if !self.eat(",") {
+                            return Err(self.error("expected `,` or `)`"));
+                        }
+                    }
+                }
+                if !function.accepts(args.len()) {
+                    return Err(ExpressionError {
+                        position: start,
+                        message: format!("wrong number of arguments for `{}`", name),
+                    });
+                }
+                return Ok(Node::Call(function, args));
+            }
+
+            return Ok(match name {
+                "pi" => Node::Number(std::f64::consts::PI),
+                "true" => Node::Number(1.0),
+                "false" => Node::Number(0.0),
+                _ => Node::Variable(name.to_string()),
+            });
+        }
+
+        Err(self.error("unexpected token"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn evaluate(source: &str, variables: &Variables) -> Option<f64> {
+        Expression::parse(source).unwrap().evaluate(variables)
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        let variables = Variables::new();
+        assert_eq!(evaluate("1 + 2 * 3", &variables), Some(7.0));
+        assert_eq!(evaluate("(1 + 2) * 3", &variables), Some(9.0));
+        assert_eq!(evaluate("2 ^ 3 ^ 2", &variables), Some(512.0));
+        assert_eq!(evaluate("-2 ^ 2", &variables), Some(-4.0));
+        assert_eq!(evaluate("7 % 3 - 1e-1", &variables), Some(0.9));
+        assert_eq!(evaluate("1.5E2 / 3", &variables), Some(50.0));
+        assert_eq!(evaluate("pi", &variables), Some(std::f64::consts::PI));
+    }
+
+    #[test]
+    fn test_logic_and_functions() {
+        let mut variables = Variables::new();
+        variables.set("stage", 1.0);
+        variables.set("metrics.branch_count", 4.0);
+        assert_eq!(
+            evaluate("stage >= 1 && !(stage == 2)", &variables),
+            Some(1.0)
+        );
+        assert_eq!(evaluate("stage < 1 || stage != 1", &variables), Some(0.0));
+        assert_eq!(
+            evaluate("if(metrics.branch_count % 2 == 0, 0.35, 0.45)", &variables),
+            Some(0.35)
+        );
+        assert_eq!(evaluate("clamp(max(1, 5, 3), 0, 4)", &variables), Some(4.0));
+        // the inverted or NaN bounds of clamp are errors.
+        assert_eq!(evaluate("clamp(2, 3, stage)", &variables), None);
+        assert_eq!(evaluate("clamp(2, 0, sqrt(-1))", &variables), None);
+        assert_eq!(evaluate("clamp(2, stage, stage)", &variables), Some(1.0));
+        assert_eq!(evaluate("min(stage, sqrt(4))", &variables), Some(1.0));
+        // the branch not selected may refer undefined variables.
+        assert_eq!(evaluate("if(1, 2, undefined)", &variables), Some(2.0));
+        assert_eq!(evaluate("undefined + 1", &variables), None);
+    }
+
+    #[test]
+    fn test_variables_and_errors() {
+        let expression = Expression::parse("b * a + if(c, a, 0)").unwrap();
+        assert_eq!(expression.variables(), vec!["a", "b", "c"]);
+
+        assert_eq!(Expression::parse("1 +").unwrap_err().position, 3);
+        assert_eq!(Expression::parse("(1 + 2").unwrap_err().position, 6);
+        assert!(Expression::parse("foo(1)").is_err());
+        assert!(Expression::parse("pow(1)").is_err());
+        assert!(Expression::parse("1 2").is_err());
+    }
+}
@@ -0,0 +1,739 @@
+//! Rules provider driven by configurations.
+//!
+//! A configuration defines road classes. Each class has a condition to be selected,
+//! expressions of the fields of `TransportRules`, and an expression of the priority.
+//! Expressions refer to the variables of the growth context and the inputs given by
+//! `ConfigInputProvider` (e.g. population density).
+//!
+//! Configurations are loaded from TOML, JSON or RON with the features
+//! `config-toml`, `config-json` and `config-ron`.
+//!
+//! ```toml
+//! inputs = ["population_density"]
+//!
+//! [[classes]]
+//! name = "highway"
+//! when = "stage == 0"
+//! priority = "population_density + 1e5"
+//!
+//! [classes.rules]
+//! path_normal_length = "if(metrics.branch_count % 2 == 0, 0.35, 0.45)"
+//! path_slope_elevation_diff_limit = "sqrt(path_length) * 2 + 1"
+//! "branch_rules.branch_density" = "0.2 + population_density * 0.8"
+//! "bridge_rules.check_step" = 15
+//! ```
+
+use std::{collections::BTreeMap, sync::Arc};
+
+use crate::core::geometry::site::Site;
+
+use super::{
+    context::GrowthContext,
+    params::{
+        priority::PathPrioritizationFactors,
+        rules::{isoline::IsolineTarget, slope::SlopePath, ElevationDiffLimit, TransportRules},
+    },
+    traits::{PathPrioritizator, TransportRulesProvider},
+};
+
+use expression::{Expression, ExpressionError, Variables};
+
+pub mod expression;
+
+/// Variables defined from the growth context.
+const CONTEXT_VARIABLES: [&str; 12] = [
+    "x",
+    "y",
+    "elevation",
+    "stage",
+    "heading",
+    "degree",
+    "road_density",
+    "nearest_path_distance",
+    "metrics.extend_count",
+    "metrics.extend_count_since_last_staged",
+    "metrics.extend_count_since_last_branched",
+    "metrics.branch_count",
+];
+
+/// Variables defined only in the priority expressions.
+const PRIORITY_VARIABLES: [&str; 5] = [
+    "path_length",
+    "creates_bridge",
+    "end_x",
+    "end_y",
+    "end_road_density",
+];
+
+/// Variable defined only in the expression of the slope limit.
+const SLOPE_VARIABLE: &str = "path_length";
+
+/// The field of the slope limit, which is evaluated for each path with `path_length`.
+const SLOPE_FIELD: &str = "path_slope_elevation_diff_limit";
+
+type FieldSetter = fn(&mut TransportRules, f64);
+
+/// Fields of `TransportRules` which can be set by expressions.
+///
+/// Integer fields are rounded, and `isoline_rules.coastline` is enabled by a non-zero value.
+const RULE_FIELDS: [(&str, FieldSetter); 21] = [
+    ("path_normal_length", |r, v| r.path_normal_length = v),
+    ("path_extra_length_for_intersection", |r, v| {
+        r.path_extra_length_for_intersection = v
+    }),
+    ("path_grade_separation_elevation_diff_threshold", |r, v| {
+        r.path_grade_separation_elevation_diff_threshold = v
+    }),
+    ("branch_rules.branch_density", |r, v| {
+        r.branch_rules.branch_density = v
+    }),
+    ("branch_rules.staging_probability", |r, v| {
+        r.branch_rules.staging_probability = v
+    }),
+    ("path_direction_rules.max_radian", |r, v| {
+        r.path_direction_rules.max_radian = v
+    }),
+    ("path_direction_rules.comparison_step", |r, v| {
+        r.path_direction_rules.comparison_step = to_count(v)
+    }),
+    ("path_direction_rules.contour_affinity", |r, v| {
+        r.path_direction_rules.contour_affinity = v
+    }),
+    ("bridge_rules.max_bridge_length", |r, v| {
+        r.bridge_rules.max_bridge_length = v
+    }),
+    ("bridge_rules.check_step", |r, v| {
+        r.bridge_rules.check_step = to_count(v)
+    }),
+    ("switchback_rules.max_hairpins", |r, v| {
+        r.switchback_rules.max_hairpins = to_count(v)
+    }),
+    ("switchback_rules.min_hairpin_spacing", |r, v| {
+        r.switchback_rules.min_hairpin_spacing = v
+    }),
+    ("switchback_rules.check_step", |r, v| {
+        r.switchback_rules.check_step = to_count(v)
+    }),
+    ("isoline_rules.target_elevation", |r, v| {
+        r.isoline_rules.target = Some(IsolineTarget::Elevation(v))
+    }),
+    ("isoline_rules.coastline", |r, v| {
+        if v != 0.0 {
+            r.isoline_rules.target = Some(IsolineTarget::Coastline)
+        }
+    }),
+    ("isoline_rules.offset", |r, v| r.isoline_rules.offset = v),
+    ("isoline_rules.check_step", |r, v| {
+        r.isoline_rules.check_step = to_count(v)
+    }),
+    ("junction_rules.min_angle", |r, v| {
+        r.junction_rules.min_angle = v
+    }),
+    ("junction_rules.min_intersection_spacing", |r, v| {
+        r.junction_rules.min_intersection_spacing = v
+    }),
+    ("clearance_rules.min_parallel_clearance", |r, v| {
+        r.clearance_rules.min_parallel_clearance = v
+    }),
+    ("clearance_rules.parallel_max_radian", |r, v| {
+        r.clearance_rules.parallel_max_radian = v
+    }),
+];
+
+fn to_count(value: f64) -> usize {
+    value.round().max(0.0) as usize
+}
+
+/// Error in loading a configuration.
+#[derive(Debug)]
+pub enum ConfigError {
+    /// Failed to read the file.
+    Io(std::io::Error),
+    /// Failed to deserialize the configuration.
+    Format(String),
+    /// The format of the file is not supported or not enabled by the features.
+    UnsupportedFormat(String),
+    /// Failed to parse the expression of the field.
+    Expression {
+        class: String,
+        field: String,
+        error: ExpressionError,
+    },
+    /// The field is not a field of `TransportRules`.
+    UnknownField { class: String, field: String },
+    /// The expression of the field refers the variable which is neither built-in nor an input.
+    UnknownVariable {
+        class: String,
+        field: String,
+        name: String,
+    },
+}
+
+impl std::fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConfigError::Io(error) => write!(f, "failed to read the configuration: {}", error),
+            ConfigError::Format(message) => write!(f, "invalid configuration: {}", message),
+            ConfigError::UnsupportedFormat(format) => {
+                write!(f, "unsupported configuration format: {}", format)
+            }
+            ConfigError::Expression {
+                class,
+                field,
+                error,
+            } => write!(
+                f,
+                "invalid expression of `{}` in `{}`: {}",
+                field, class, error
+            ),
+            ConfigError::UnknownField { class, field } => {
+                write!(f, "unknown field `{}` in `{}`", field, class)
+            }
+            ConfigError::UnknownVariable { class, field, name } => write!(
+                f,
+                "unknown variable `{}` in the expression of `{}` in `{}`",
+                name, field, class
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+/// Value of a field, a number or an expression.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "config", derive(serde::Deserialize))]
+#[cfg_attr(feature = "config", serde(untagged))]
+pub enum ValueConfig {
+    Number(f64),
+    Expression(String),
+}
+
+/// Configuration of a road class.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "config", derive(serde::Deserialize))]
+#[cfg_attr(feature = "config", serde(deny_unknown_fields))]
+pub struct ClassConfig {
+    pub name: String,
+    /// Condition to select the class. If omitted, the class is always selected.
+    pub when: Option<String>,
+    /// Priority of the paths. If omitted, the priority is 0.
+    pub priority: Option<String>,
+    /// Values of the fields of `TransportRules`.
+    ///
+    /// Fields of nested rules are specified with the dotted name (e.g. `branch_rules.branch_density`),
+    /// and omitted fields are the default values.
+    #[cfg_attr(feature = "config", serde(default))]
+    pub rules: BTreeMap<String, ValueConfig>,
+}
+
+/// Configuration of rules.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "config", derive(serde::Deserialize))]
+#[cfg_attr(feature = "config", serde(deny_unknown_fields))]
+pub struct RulesConfig {
+    /// Names of the inputs given by `ConfigInputProvider`.
+    #[cfg_attr(feature = "config", serde(default))]
+    pub inputs: Vec<String>,
+    /// Road classes, where the first class whose condition holds is selected.
+    pub classes: Vec<ClassConfig>,
+}
+
+impl RulesConfig {
+    /// Load the configuration from a TOML string.
+    #[cfg(feature = "config-toml")]
+    pub fn from_toml_str(source: &str) -> Result<Self, ConfigError> {
+        toml::from_str(source).map_err(|error| ConfigError::Format(error.to_string()))
+    }
+
+    /// Load the configuration from a JSON string.
+    #[cfg(feature = "config-json")]
+    pub fn from_json_str(source: &str) -> Result<Self, ConfigError> {
+        serde_json::from_str(source).map_err(|error| ConfigError::Format(error.to_string()))
+    }
+
+    /// Load the configuration from a RON string.
+    #[cfg(feature = "config-ron")]
+    pub fn from_ron_str(source: &str) -> Result<Self, ConfigError> {
+        ron::from_str(source).map_err(|error| ConfigError::Format(error.to_string()))
+    }
+
+    /// Load the configuration from a file, whose format is determined by the extension.
+    pub fn from_file<P>(path: P) -> Result<Self, ConfigError>
+    where
+        P: AsRef<std::path::Path>,
+    {
+        let path = path.as_ref();
+        let extension = path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        let source = std::fs::read_to_string(path).map_err(ConfigError::Io)?;
+        match extension.as_str() {
+            #[cfg(feature = "config-toml")]
+            "toml" => Self::from_toml_str(&source),
+            #[cfg(feature = "config-json")]
+            "json" => Self::from_json_str(&source),
+            #[cfg(feature = "config-ron")]
+            "ron" => Self::from_ron_str(&source),
+            _ => {
+                let _ = source;
+                Err(ConfigError::UnsupportedFormat(extension))
+            }
+        }
+    }
+}
+
+/// Provider of the inputs referred by expressions.
+pub trait ConfigInputProvider {
+    /// Get the value of the input at the site.
+    fn get_input(&self, name: &str, site: &Site) -> Option<f64>;
+}
+
+impl<F> ConfigInputProvider for F
+where
+    F: Fn(&str, &Site) -> Option<f64>,
+{
+    fn get_input(&self, name: &str, site: &Site) -> Option<f64> {
+        self(name, site)
+    }
+}
+
+/// Road class compiled from `ClassConfig`.
+#[derive(Debug, Clone)]
+struct RoadClass {
+    name: String,
+    when: Option<Expression>,
+    priority: Option<Expression>,
+    rules: Vec<(FieldSetter, Expression)>,
+    slope_limit: Option<Arc<Expression>>,
+}
+
+/// Rules provider and prioritizator defined by a configuration.
+pub struct ConfigRulesProvider<'a, IP>
+where
+    IP: ConfigInputProvider,
+{
+    input_provider: &'a IP,
+    inputs: Vec<String>,
+    classes: Vec<RoadClass>,
+}
+
+impl<'a, IP> ConfigRulesProvider<'a, IP>
+where
+    IP: ConfigInputProvider,
+{
+    /// Create a provider from the configuration.
+    ///
+    /// All expressions are parsed and checked to refer only the defined variables.
+    pub fn new(config: &RulesConfig, input_provider: &'a IP) -> Result<Self, ConfigError> {
+        let classes = config
+            .classes
+            .iter()
+            .map(|class| Self::compile_class(class, &config.inputs))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self {
+            input_provider,
+            inputs: config.inputs.clone(),
+            classes,
+        })
+    }
+
+    fn compile_class(class: &ClassConfig, inputs: &[String]) -> Result<RoadClass, ConfigError> {
+        let compile = |field: &str, value: &ValueConfig, extra: &[&str]| {
+            let expression = match value {
+                ValueConfig::Number(value) => Expression::constant(*value),
+                ValueConfig::Expression(source) => {
+                    Expression::parse(source).map_err(|error| ConfigError::Expression {
+                        class: class.name.clone(),
+                        field: field.to_string(),
+                        error,
+                    })?
+                }
+            };
+            let unknown = expression.variables().into_iter().find(|name| {
+                !CONTEXT_VARIABLES.contains(name)
+                    && !extra.contains(name)
+                    && !inputs.iter().any(|input| input == name)
+            });
+            if let Some(name) = unknown {
+                return Err(ConfigError::UnknownVariable {
+                    class: class.name.clone(),
+                    field: field.to_string(),
+                    name: name.to_string(),
+                });
+            }
+            Ok(expression)
+        };
+        let compile_source = |field: &str, source: &Option<String>, extra: &[&str]| {
+            source
+                .as_ref()
+                .map(|source| compile(field, &ValueConfig::Expression(source.clone()), extra))
+                .transpose()
+        };
+
+        let mut rules = Vec::new();
+        let mut slope_limit = None;
+        for (field, value) in class.rules.iter() {
+            if field == SLOPE_FIELD {
+                slope_limit = Some(Arc::new(compile(field, value, &[SLOPE_VARIABLE])?));
+                continue;
+            }
+            let setter = RULE_FIELDS
+                .iter()
+                .find(|(name, _)| name == field)
+                .map(|(_, setter)| *setter)
+                .ok_or_else(|| ConfigError::UnknownField {
+                    class: class.name.clone(),
+                    field: field.clone(),
+                })?;
+            rules.push((setter, compile(field, value, &[])?));
+        }
+
+        Ok(RoadClass {
+            name: class.name.clone(),
+            when: compile_source("when", &class.when, &[])?,
+            priority: compile_source("priority", &class.priority, &PRIORITY_VARIABLES)?,
+            rules,
+            slope_limit,
+        })
+    }
+
+    /// Get the variables of the context, with the inputs at the site.
+    fn variables(&self, context: &GrowthContext, site_inputs: &Site) -> Option<Variables> {
+        let mut variables = Variables::new();
+        let (node, metrics) = (context.node, context.metrics);
+        variables.set("x", node.site.x);
+        variables.set("y", node.site.y);
+        variables.set("elevation", node.elevation);
+        variables.set("stage", context.stage.as_num() as f64);
+        variables.set("heading", context.heading.radian());
+        variables.set("degree", context.degree as f64);
+        variables.set("road_density", context.road_density.unwrap_or(0.0));
+        variables.set(
+            "nearest_path_distance",
+            context.nearest_path_distance.unwrap_or(f64::INFINITY),
+        );
+        variables.set("metrics.extend_count", metrics.extend_count as f64);
+        variables.set(
+            "metrics.extend_count_since_last_staged",
+            metrics.extend_count_since_last_staged as f64,
+        );
+        variables.set(
+            "metrics.extend_count_since_last_branched",
+            metrics.extend_count_since_last_branched as f64,
+        );
+        variables.set("metrics.branch_count", metrics.branch_count as f64);
+        for input in self.inputs.iter() {
+            variables.set(input, self.input_provider.get_input(input, site_inputs)?);
+        }
+        Some(variables)
+    }
+
+    /// Select the class whose condition holds.
+    fn select_class(&self, variables: &Variables) -> Option<&RoadClass> {
+        self.classes.iter().find(|class| {
+            class.when.as_ref().is_none_or(|when| {
+                when.evaluate(variables)
+                    .is_some_and(|condition| condition != 0.0)
+            })
+        })
+    }
+
+    /// Get the name of the class selected in the context.
+    pub fn get_class_name(&self, context: &GrowthContext) -> Option<&str> {
+        let variables = self.variables(context, &context.node.site)?;
+        self.select_class(&variables)
+            .map(|class| class.name.as_str())
+    }
+}
+
+impl<IP> TransportRulesProvider for ConfigRulesProvider<'_, IP>
+where
+    IP: ConfigInputProvider,
+{
+    fn get_rules(&self, context: &GrowthContext) -> Option<TransportRules> {
+        let variables = self.variables(context, &context.node.site)?;
+        let class = self.select_class(&variables)?;
+
+        let mut rules = TransportRules::default();
+        for (setter, expression) in class.rules.iter() {
+            setter(&mut rules, expression.evaluate(&variables)?);
+        }
+        if let Some(expression) = class.slope_limit.clone() {
+            rules.path_slope_elevation_diff_limit =
+                ElevationDiffLimit::from_fn(move |path: &SlopePath| {
+                    let mut variables = variables.clone();
+                    variables.set(SLOPE_VARIABLE, path.path_length);
+                    expression.evaluate(&variables).unwrap_or(f64::NEG_INFINITY)
+                });
+        }
+        Some(rules)
+    }
+}
+
+impl<IP> PathPrioritizator for ConfigRulesProvider<'_, IP>
+where
+    IP: ConfigInputProvider,
+{
+    /// The inputs are evaluated at the end site of the path.
+    fn prioritize(&self, factors: PathPrioritizationFactors) -> Option<f64> {
+        let mut variables = self.variables(factors.context, &factors.site_end)?;
+        let class = self.select_class(&variables)?;
+        let priority = if let Some(priority) = class.priority.as_ref() {
+            priority
+        } else {
+            return Some(0.0);
+        };
+
+        variables.set("path_length", factors.path_length);
+        variables.set(
+            "creates_bridge",
+            f64::from(u8::from(factors.creates_bridge)),
+        );
+        variables.set("end_x", factors.site_end.x);
+        variables.set("end_y", factors.site_end.y);
+        variables.set("end_road_density", factors.road_density.unwrap_or(0.0));
+        priority.evaluate(&variables)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        core::container::path_network::{NodeId, PathNetwork},
+        core::geometry::angle::Angle,
+        transport::{
+            node::TransportNode,
+            params::{metrics::PathMetrics, numeric::Stage},
+        },
+    };
+
+    use super::*;
+
+    fn create_config() -> RulesConfig {
+        let rules = |entries: &[(&str, ValueConfig)]| {
+            entries
+                .iter()
+                .map(|(field, value)| (field.to_string(), value.clone()))
+                .collect()
+        };
+        let expression = |source: &str| ValueConfig::Expression(source.to_string());
+        RulesConfig {
+            inputs: vec!["population_density".to_string()],
+            classes: vec![
+                ClassConfig {
+                    name: "highway".to_string(),
+                    when: Some("stage == 0".to_string()),
+                    priority: Some("population_density * 10 + path_length".to_string()),
+                    rules: rules(&[
+                        (
+                            "path_normal_length",
+                            expression("if(metrics.branch_count % 2 == 0, 0.35, 0.45)"),
+                        ),
+                        ("bridge_rules.check_step", ValueConfig::Number(15.0)),
+                        (SLOPE_FIELD, expression("path_length * 2")),
+                    ]),
+                },
+                ClassConfig {
+                    name: "street".to_string(),
+                    when: None,
+                    priority: None,
+                    rules: rules(&[(
+                        "branch_rules.branch_density",
+                        expression("0.01 + population_density * 0.99"),
+                    )]),
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn test_config_rules_provider() {
+        let input_provider =
+            |name: &str, site: &Site| (name == "population_density").then_some(site.x);
+        let provider = ConfigRulesProvider::new(&create_config(), &input_provider).unwrap();
+
+        let network = PathNetwork::new();
+        let node = TransportNode::new(Site::new(0.5, 0.0), 0.0, Stage::from_num(0), false);
+        let metrics = PathMetrics::default();
+        let context = |stage: usize| GrowthContext {
+            node_id: NodeId::new(0),
+            node: &node,
+            heading: Angle::new(0.0),
+            stage: Stage::from_num(stage),
+            metrics: &metrics,
+            degree: 0,
+            nearest_path_distance: None,
+            origin: None,
+            road_density: None,
+            network: &network,
+        };
+
+        let highway = context(0);
+        assert_eq!(provider.get_class_name(&highway), Some("highway"));
+        let rules = provider.get_rules(&highway).unwrap();
+        assert_eq!(rules.path_normal_length, 0.35);
+        assert_eq!(rules.bridge_rules.check_step, 15);
+        let end = TransportNode::new(Site::new(1.5, 0.0), 1.9, Stage::from_num(0), false);
+        assert!(rules
+            .path_slope_elevation_diff_limit
+            .check_slope(&SlopePath::new(node, end)));
+        let end = TransportNode::new(Site::new(1.5, 0.0), 2.1, Stage::from_num(0), false);
+        assert!(!rules
+            .path_slope_elevation_diff_limit
+            .check_slope(&SlopePath::new(node, end)));
+
+        let priority = provider.prioritize(PathPrioritizationFactors {
+            site_start: node.site,
+            site_end: Site::new(1.5, 0.0),
+            path_length: 1.0,
+            stage: Stage::from_num(0),
+            creates_bridge: false,
+            road_density: None,
+            context: &highway,
+        });
+        assert_eq!(priority, Some(16.0));
+
+        let street = context(1);
+        assert_eq!(provider.get_class_name(&street), Some("street"));
+        let rules = provider.get_rules(&street).unwrap();
+        assert_eq!(rules.branch_rules.branch_density, 0.01 + 0.5 * 0.99);
+        assert_eq!(rules.path_normal_length, 0.0);
+    }
+
+    #[test]
+    fn test_config_errors() {
+        let input_provider = |_: &str, _: &Site| None;
+
+        let mut config = create_config();
+        config.classes[1]
+            .rules
+            .insert("no_such_field".to_string(), ValueConfig::Number(1.0));
+        assert!(matches!(
+            ConfigRulesProvider::new(&config, &input_provider),
+            Err(ConfigError::UnknownField { .. })
+        ));
+
+        let mut config = create_config();
+        config.classes[1].when = Some("elevation > altitude".to_string());
+        assert!(matches!(
+            ConfigRulesProvider::new(&config, &input_provider),
+            Err(ConfigError::UnknownVariable { name, .. }) if name == "altitude"
+        ));
+
+        // `path_length` is only available in the priority and the slope limit.
+        let mut config = create_config();
+        config.classes[1].rules.insert(
+            "path_normal_length".to_string(),
+            ValueConfig::Expression("path_length".to_string()),
+        );
+        assert!(ConfigRulesProvider::new(&config, &input_provider).is_err());
+
+        let mut config = create_config();
+        config.classes[0].priority = Some("1 +".to_string());
+        assert!(matches!(
+            ConfigRulesProvider::new(&config, &input_provider),
+            Err(ConfigError::Expression { .. })
+        ));
+    }
+
+    #[cfg(feature = "config-toml")]
+    #[test]
+    fn test_from_toml_str() {
+        let config = RulesConfig::from_toml_str(
+            r#"
+            inputs = ["population_density"]
+
+            [[classes]]
+            name = "highway"
+            when = "stage == 0"
+            priority = "population_density * 10 + path_length"
+
+            [classes.rules]
+            path_normal_length = "if(metrics.branch_count % 2 == 0, 0.35, 0.45)"
+            "bridge_rules.check_step" = 15
+            path_slope_elevation_diff_limit = "path_length * 2"
+
+            [[classes]]
+            name = "street"
+
+            [classes.rules]
+            "branch_rules.branch_density" = "0.01 + population_density * 0.99"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config, create_config());
+    }
+
+    #[cfg(feature = "config-json")]
+    #[test]
+    fn test_from_json_str() {
+        let config = RulesConfig::from_json_str(
+            r#"{
+                "inputs": ["population_density"],
+                "classes": [
+                    {
+                        "name": "highway",
+                        "when": "stage == 0",
+                        "priority": "population_density * 10 + path_length",
+                        "rules": {
+                            "path_normal_length": "if(metrics.branch_count % 2 == 0, 0.35, 0.45)",
+                            "bridge_rules.check_step": 15,
+                            "path_slope_elevation_diff_limit": "path_length * 2"
+                        }
+                    },
+                    {
+                        "name": "street",
+                        "rules": {
+                            "branch_rules.branch_density": "0.01 + population_density * 0.99"
+                        }
+                    }
+                ]
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(config, create_config());
+
+        assert!(matches!(
+            RulesConfig::from_json_str(r#"{ "classes": [], "unknown": 0 }"#),
+            Err(ConfigError::Format(_))
+        ));
+    }
+
+    #[cfg(feature = "config-ron")]
+    #[test]
+    fn test_from_ron_str() {
+        let config = RulesConfig::from_ron_str(
+            r#"(
+                inputs: ["population_density"],
+                classes: [
+                    (
+                        name: "highway",
+                        when: Some("stage == 0"),
+                        priority: Some("population_density * 10 + path_length"),
+                        rules: {
+                            "path_normal_length": "if(metrics.branch_count % 2 == 0, 0.35, 0.45)",
+                            "bridge_rules.check_step": 15,
+                            "path_slope_elevation_diff_limit": "path_length * 2",
+                        },
+                    ),
+                    (
+                        name: "street",
+                        rules: {
+                            "branch_rules.branch_density": "0.01 + population_density * 0.99",
+                        },
+                    ),
+                ],
+            )"#,
+        )
+        .unwrap();
+        assert_eq!(config, create_config());
+
+        assert!(matches!(
+            RulesConfig::from_ron_str("(classes: [], unknown: 0)"),
+            Err(ConfigError::Format(_))
+        ));
+    }
+}
@@ -0,0 +1,175 @@
+use crate::core::geometry::{line_segment::LineSegment, polygon::Polygon, site::Site};
+
+/// Tolerance to ignore the crossings of the boundary at the start of the path.
+const CLIP_EPSILON: f64 = 1e-9;
+
+/// Zone where paths are not allowed to be constructed (parks, airports, lakes, etc.).
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeepOutZone {
+    /// The area of the zone.
+    pub polygon: Polygon,
+    /// Whether bridges are allowed to cross over the zone.
+    pub allows_bridge: bool,
+}
+
+impl KeepOutZone {
+    /// Create a new keep-out zone which doesn't allow bridges.
+    pub fn new(polygon: Polygon) -> Self {
+        Self {
+            polygon,
+            allows_bridge: false,
+        }
+    }
+
+    /// Set whether bridges are allowed to cross over the zone.
+    pub fn allows_bridge(mut self, allows_bridge: bool) -> Self {
+        self.allows_bridge = allows_bridge;
+        self
+    }
+
+    /// Check if the path is allowed for this zone.
+    fn permits_path(&self, line: &LineSegment, creates_bridge: bool) -> bool {
+        if self.polygon.contains(&line.0) || self.polygon.contains(&line.1) {
+            return false;
+        }
+        if self.polygon.get_intersections(line).is_empty() {
+            return true;
+        }
+        creates_bridge && self.allows_bridge
+    }
+}
+
+/// Spatial constraints of the growth of the network.
+///
+/// With `Default` values, the growth is not constrained.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct GrowthConstraints {
+    /// The boundary which the network is bounded in.
+    pub boundary: Option<Polygon>,
+    /// Zones where paths are not allowed to be constructed.
+    pub keep_out_zones: Vec<KeepOutZone>,
+}
+
+impl GrowthConstraints {
+    /// Set the boundary which the network is bounded in.
+    pub fn boundary(mut self, boundary: Polygon) -> Self {
+        self.boundary = Some(boundary);
+        self
+    }
+
+    /// Add a zone where paths are not allowed to be constructed.
+    pub fn keep_out_zone(mut self, zone: KeepOutZone) -> Self {
+        self.keep_out_zones.push(zone);
+        self
+    }
+
+    /// Check if a node can be placed on the site.
+    pub fn allows_site(&self, site: &Site) -> bool {
+        if let Some(boundary) = &self.boundary {
+            if !boundary.contains(site) {
+                return false;
+            }
+        }
+        self.keep_out_zones
+            .iter()
+            .all(|zone| !zone.polygon.contains(site))
+    }
+
+    /// Clip the path at the boundary.
+    ///
+    /// Returns the end site of the clipped path.
+    /// If the path can't be placed inside the boundary at all, return None.
+    pub fn clip_path(&self, site_start: Site, site_end: Site) -> Option<Site> {
+        let boundary = if let Some(boundary) = &self.boundary {
+            boundary
+        } else {
+            return Some(site_end);
+        };
+
+        let crossing = boundary
+            .get_intersections(&LineSegment::new(site_start, site_end))
+            .into_iter()
+            .find(|site| site.distance(&site_start) > CLIP_EPSILON);
+
+        if let Some(crossing) = crossing {
+            Some(crossing)
+        } else if boundary.contains(&site_end) {
+            Some(site_end)
+        } else {
+            None
+        }
+    }
+
+    /// Check if the path can be constructed without clipping.
+    pub fn permits_path(&self, site_start: Site, site_end: Site, creates_bridge: bool) -> bool {
+        let site_clipped = if let Some(site) = self.clip_path(site_start, site_end) {
+            site
+        } else {
+            return false;
+        };
+        if site_clipped.distance(&site_end) > CLIP_EPSILON {
+            return false;
+        }
+
+        let line = LineSegment::new(site_start, site_end);
+        self.keep_out_zones
+            .iter()
+            .all(|zone| zone.permits_path(&line, creates_bridge))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rect(x0: f64, y0: f64, x1: f64, y1: f64) -> Polygon {
+        Polygon::new(vec![
+            Site::new(x0, y0),
+            Site::new(x1, y0),
+            Site::new(x1, y1),
+            Site::new(x0, y1),
+        ])
+    }
+
+    #[test]
+    fn test_clip_path() {
+        let constraints = GrowthConstraints::default().boundary(rect(0.0, 0.0, 10.0, 10.0));
+
+        // inside
+        let end = constraints.clip_path(Site::new(5.0, 5.0), Site::new(6.0, 5.0));
+        assert_eq!(end, Some(Site::new(6.0, 5.0)));
+
+        // clipped at the boundary
+        let end = constraints.clip_path(Site::new(9.0, 5.0), Site::new(11.0, 5.0));
+        assert_eq!(end, Some(Site::new(10.0, 5.0)));
+
+        // leaving the boundary from the boundary
+        let end = constraints.clip_path(Site::new(10.0, 5.0), Site::new(11.0, 5.0));
+        assert_eq!(end, None);
+
+        // entering the boundary from the boundary
+        let end = constraints.clip_path(Site::new(10.0, 5.0), Site::new(9.0, 5.0));
+        assert_eq!(end, Some(Site::new(9.0, 5.0)));
+    }
+
+    #[test]
+    fn test_keep_out_zone() {
+        let constraints = GrowthConstraints::default()
+            .keep_out_zone(KeepOutZone::new(rect(2.0, 2.0, 4.0, 4.0)))
+            .keep_out_zone(KeepOutZone::new(rect(6.0, 2.0, 8.0, 4.0)).allows_bridge(true));
+
+        assert!(!constraints.allows_site(&Site::new(3.0, 3.0)));
+        assert!(constraints.allows_site(&Site::new(5.0, 3.0)));
+
+        // ends in the zone
+        assert!(!constraints.permits_path(Site::new(1.0, 3.0), Site::new(3.0, 3.0), false));
+        // crosses the zone
+        assert!(!constraints.permits_path(Site::new(1.0, 3.0), Site::new(5.0, 3.0), false));
+        assert!(!constraints.permits_path(Site::new(1.0, 3.0), Site::new(5.0, 3.0), true));
+        // crosses the zone which allows bridges
+        assert!(!constraints.permits_path(Site::new(5.0, 3.0), Site::new(9.0, 3.0), false));
+        assert!(constraints.permits_path(Site::new(5.0, 3.0), Site::new(9.0, 3.0), true));
+        // passes by the zones
+        assert!(constraints.permits_path(Site::new(1.0, 5.0), Site::new(9.0, 5.0), false));
+    }
+}
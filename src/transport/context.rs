@@ -0,0 +1,54 @@
+use crate::core::{
+    container::path_network::{NodeId, PathNetwork},
+    geometry::angle::Angle,
+};
+
+use super::{
+    node::TransportNode,
+    params::{
+        metrics::{GrowthMetrics, PathMetrics},
+        numeric::Stage,
+    },
+};
+
+/// Context of the path to be extended from a node, passed to the providers.
+#[derive(Debug)]
+pub struct GrowthContext<'a, M = PathMetrics>
+where
+    M: GrowthMetrics,
+{
+    /// The id of the node which the path is extended from.
+    pub node_id: NodeId,
+    /// The node which the path is extended from.
+    pub node: &'a TransportNode,
+    /// The expected direction of the path.
+    pub heading: Angle,
+    /// The stage of the path.
+    pub stage: Stage,
+    /// The metrics of the path.
+    pub metrics: &'a M,
+    /// The number of paths connected to the node.
+    pub degree: usize,
+    /// The distance from the node to the nearest path not connected to the node.
+    pub nearest_path_distance: Option<f64>,
+    /// The origin node which the path descends from.
+    ///
+    /// This is `None` for the paths which don't descend from any origin (e.g. highways).
+    pub origin: Option<NodeId>,
+    /// The density of paths around the node, if the builder tracks the road density.
+    pub road_density: Option<f64>,
+    /// The path network at the moment.
+    pub network: &'a PathNetwork<TransportNode>,
+}
+
+// Implemented manually because the metrics are only borrowed and don't need to be `Copy`.
+impl<M> Clone for GrowthContext<'_, M>
+where
+    M: GrowthMetrics,
+{
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<M> Copy for GrowthContext<'_, M> where M: GrowthMetrics {}
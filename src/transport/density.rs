@@ -0,0 +1,108 @@
+use std::collections::BTreeMap;
+
+use crate::core::{container::path_network::PathNetwork, geometry::site::Site};
+
+use super::node::TransportNode;
+
+/// Accumulator of the length of paths per grid cell.
+///
+/// The density is the length of paths per unit area, which can be used to saturate the growth
+/// once the target density is reached.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RoadDensity {
+    cell_size: f64,
+    lengths: BTreeMap<(i64, i64), f64>,
+}
+
+impl RoadDensity {
+    /// Create an empty accumulator with the size of grid cells.
+    pub fn new(cell_size: f64) -> Self {
+        Self {
+            cell_size,
+            lengths: BTreeMap::new(),
+        }
+    }
+
+    /// Create an accumulator from the paths in the network.
+    pub fn from_network(network: &PathNetwork<TransportNode>, cell_size: f64) -> Self {
+        let mut density = Self::new(cell_size);
+        network.nodes_iter().for_each(|(node_id, node)| {
+            if let Some(neighbors) = network.neighbors_iter(node_id) {
+                neighbors
+                    .filter(|(neighbor_id, _)| node_id < *neighbor_id)
+                    .for_each(|(_, neighbor)| density.add_path(node.site, neighbor.site));
+            }
+        });
+        density
+    }
+
+    /// Get the size of grid cells.
+    pub fn cell_size(&self) -> f64 {
+        self.cell_size
+    }
+
+    fn cell_of(&self, site: &Site) -> (i64, i64) {
+        (
+            (site.x / self.cell_size).floor() as i64,
+            (site.y / self.cell_size).floor() as i64,
+        )
+    }
+
+    /// Distribute the length of the path to the cells it passes.
+    fn accumulate(&mut self, start: Site, end: Site, sign: f64) {
+        if self.cell_size <= 0.0 {
+            return;
+        }
+        let length = start.distance(&end);
+        let pieces = ((length / (self.cell_size * 0.5)).ceil() as usize).max(1);
+        (0..pieces).for_each(|i| {
+            let t = (i as f64 + 0.5) / pieces as f64;
+            let site = Site::new(
+                start.x + (end.x - start.x) * t,
+                start.y + (end.y - start.y) * t,
+            );
+            let cell = self.cell_of(&site);
+            let cell_length = self.lengths.entry(cell).or_insert(0.0);
+            *cell_length = (*cell_length + sign * length / pieces as f64).max(0.0);
+        });
+    }
+
+    /// Add the length of the path.
+    pub fn add_path(&mut self, start: Site, end: Site) {
+        self.accumulate(start, end, 1.0);
+    }
+
+    /// Remove the length of the path.
+    pub fn remove_path(&mut self, start: Site, end: Site) {
+        self.accumulate(start, end, -1.0);
+    }
+
+    /// Get the density (length of paths per unit area) of the cell containing the site.
+    pub fn get_density(&self, site: &Site) -> f64 {
+        if self.cell_size <= 0.0 {
+            return 0.0;
+        }
+        self.lengths
+            .get(&self.cell_of(site))
+            .map_or(0.0, |length| length / (self.cell_size * self.cell_size))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_road_density() {
+        let mut density = RoadDensity::new(2.0);
+
+        // the path passes two cells equally.
+        density.add_path(Site::new(1.0, 1.0), Site::new(3.0, 1.0));
+        assert!((density.get_density(&Site::new(0.5, 0.5)) - 0.25).abs() < 1e-6);
+        assert!((density.get_density(&Site::new(3.5, 0.5)) - 0.25).abs() < 1e-6);
+        assert_eq!(density.get_density(&Site::new(0.5, 2.5)), 0.0);
+
+        density.remove_path(Site::new(1.0, 1.0), Site::new(3.0, 1.0));
+        assert!(density.get_density(&Site::new(0.5, 0.5)).abs() < 1e-6);
+    }
+}
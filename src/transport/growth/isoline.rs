@@ -0,0 +1,124 @@
+use crate::{
+    core::geometry::{angle::Angle, site::Site},
+    transport::{params::rules::isoline::IsolineRules, traits::TerrainProvider},
+};
+
+/// Calculate how far the site heading to `angle` deviates from the offset line of the iso-line.
+///
+/// The iso-line is probed in the perpendicular directions on both sides of the heading.
+/// If the iso-line is not found in the probed range, the site is regarded as far from the iso-line.
+/// Returns None if the iso-line rules are not active or the site itself is on the lower side.
+pub fn isoline_deviation<TP>(
+    terrain_provider: &TP,
+    rules: &IsolineRules,
+    site: &Site,
+    angle: Angle,
+) -> Option<f64>
+where
+    TP: TerrainProvider,
+{
+    let target = rules.target?;
+    if rules.check_step == 0 || target.is_below(terrain_provider.get_elevation(site)) {
+        return None;
+    }
+
+    let range = rules.offset * 2.0;
+    let distance = [angle.right_clockwise(), angle.right_counterclockwise()]
+        .iter()
+        .filter_map(|side| {
+            (1..=rules.check_step)
+                .map(|i| range * (i as f64) / (rules.check_step as f64))
+                .find(|distance| {
+                    target.is_below(terrain_provider.get_elevation(&site.extend(*side, *distance)))
+                })
+        })
+        .fold(range, f64::min);
+
+    Some((distance - rules.offset).abs())
+}
+
+/// Calculate the slope avoided by the heading to `angle` on the terrain with `gradient`.
+///
+/// The value is the maximum (the magnitude of the gradient) when the heading follows the contour line,
+/// and zero when the heading is parallel to the gradient.
+pub fn contour_alignment(gradient: (f64, f64), angle: Angle) -> f64 {
+    let slope_max = (gradient.0 * gradient.0 + gradient.1 * gradient.1).sqrt();
+    let slope_heading = (gradient.0 * angle.unit_x() + gradient.1 * angle.unit_y()).abs();
+    slope_max - slope_heading
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::transport::params::rules::isoline::IsolineTarget;
+
+    use super::*;
+
+    /// Sea is at x < 0.0, and the elevation increases to the east.
+    struct CoastTerrain;
+
+    impl TerrainProvider for CoastTerrain {
+        fn get_elevation(&self, site: &Site) -> Option<f64> {
+            if site.x < 0.0 {
+                None
+            } else {
+                Some(site.x)
+            }
+        }
+    }
+
+    #[test]
+    fn test_coastline_deviation() {
+        let rules = IsolineRules {
+            target: Some(IsolineTarget::Coastline),
+            offset: 1.0,
+            check_step: 10,
+        };
+        // heading north
+        let angle = Angle::new(0.0);
+
+        let deviation =
+            |x: f64| isoline_deviation(&CoastTerrain, &rules, &Site::new(x, 0.0), angle);
+
+        assert!(deviation(1.05).unwrap() < 0.25);
+        assert!(deviation(0.55).unwrap() > 0.3);
+        assert!(deviation(1.55).unwrap() > 0.3);
+        // far from the coast
+        assert_eq!(deviation(5.0), Some(1.0));
+        // in the sea
+        assert_eq!(deviation(-1.0), None);
+    }
+
+    #[test]
+    fn test_contour_alignment() {
+        let gradient = CoastTerrain
+            .get_gradient(&Site::new(1.0, 0.0), 0.1)
+            .unwrap();
+        assert!((gradient.0 - 1.0).abs() < 1e-6);
+        assert!(gradient.1.abs() < 1e-6);
+
+        // along the contour line
+        assert!((contour_alignment(gradient, Angle::new(0.0)) - 1.0).abs() < 1e-6);
+        // along the gradient
+        assert!(contour_alignment(gradient, Angle::new(0.5 * std::f64::consts::PI)).abs() < 1e-6);
+        assert!(contour_alignment(gradient, Angle::new(-0.5 * std::f64::consts::PI)).abs() < 1e-6);
+        // diagonal
+        let diagonal = contour_alignment(gradient, Angle::new(0.25 * std::f64::consts::PI));
+        assert!((diagonal - (1.0 - 0.5_f64.sqrt())).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_contour_deviation() {
+        let rules = IsolineRules {
+            target: Some(IsolineTarget::Elevation(3.0)),
+            offset: 1.0,
+            check_step: 10,
+        };
+        let angle = Angle::new(0.0);
+
+        let deviation =
+            |x: f64| isoline_deviation(&CoastTerrain, &rules, &Site::new(x, 0.0), angle);
+
+        assert!(deviation(4.05).unwrap() < 0.25);
+        assert_eq!(deviation(2.0), None);
+    }
+}
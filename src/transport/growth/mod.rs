@@ -1,26 +1,38 @@
 pub mod growth_type;
+pub mod isoline;
 pub mod stump;
+pub mod switchback;
 
 #[cfg(test)]
 mod tests {
+    use std::{cell::RefCell, collections::BTreeMap};
+
     use crate::{
         core::{
-            container::path_network::NodeId,
+            container::path_network::{NodeId, PathNetwork},
             geometry::{angle::Angle, site::Site},
         },
         transport::{
+            cache::{CachedPathPrioritizator, CachedTerrainProvider},
+            constraints::GrowthConstraints,
+            context::GrowthContext,
             node::TransportNode,
             params::{
                 metrics::PathMetrics,
                 numeric::Stage,
-                rules::{ElevationDiffLimit, TransportRules},
+                priority::PathPrioritizationFactors,
+                rules::{
+                    bridge::BridgeRules, direction::PathDirectionRules, junction::JunctionRules,
+                    ElevationDiffLimit, TransportRules,
+                },
             },
+            traits::{PathPrioritizator, TerrainProvider},
         },
     };
 
     use super::{
         growth_type::{GrowthTypes, NextNodeType},
-        stump::Stump,
+        stump::{JunctionDistance, Stump, StumpFailure},
     };
 
     macro_rules! assert_eq_f64 {
@@ -48,7 +60,7 @@ mod tests {
     }
     #[test]
     fn test_next_node() {
-        let nodes = vec![
+        let nodes = [
             create_node(3.0, 0.0),
             create_node(1.0, 0.0),
             create_node(0.0, 1.0),
@@ -61,7 +73,7 @@ mod tests {
             .map(|(i, node)| (node, NodeId::new(i)))
             .collect::<Vec<_>>();
 
-        let paths = vec![(0, 1), (1, 2), (2, 3)];
+        let paths = [(0, 1), (1, 2), (2, 3)];
 
         let paths_parsed = paths
             .iter()
@@ -89,7 +101,14 @@ mod tests {
             0.0,
             false,
         )
-        .determine_growth(&node_start, &nodes_parsed, &paths_parsed);
+        .determine_growth(
+            &node_start,
+            &nodes_parsed,
+            &paths_parsed,
+            &BTreeMap::new(),
+            &BTreeMap::new(),
+            &GrowthConstraints::default(),
+        );
 
         if let NextNodeType::New(node) = new.next_node {
             assert_eq_f64!(
@@ -120,7 +139,14 @@ mod tests {
             0.0,
             false,
         )
-        .determine_growth(&node_start, &nodes_parsed, &paths_parsed);
+        .determine_growth(
+            &node_start,
+            &nodes_parsed,
+            &paths_parsed,
+            &BTreeMap::new(),
+            &BTreeMap::new(),
+            &GrowthConstraints::default(),
+        );
 
         if let NextNodeType::Intersect(node, _) = intersect.next_node {
             assert_eq_f64!(node.site.distance(&Site::new(0.5, 0.5)), 0.0);
@@ -145,7 +171,14 @@ mod tests {
             0.0,
             false,
         )
-        .determine_growth(&node_start, &nodes_parsed, &paths_parsed);
+        .determine_growth(
+            &node_start,
+            &nodes_parsed,
+            &paths_parsed,
+            &BTreeMap::new(),
+            &BTreeMap::new(),
+            &GrowthConstraints::default(),
+        );
 
         if let NextNodeType::Existing(node_id) = existing.next_node {
             assert_eq!(node_id, NodeId::new(1));
@@ -169,7 +202,14 @@ mod tests {
             0.0,
             false,
         )
-        .determine_growth(&node_start, &nodes_parsed, &paths_parsed);
+        .determine_growth(
+            &node_start,
+            &nodes_parsed,
+            &paths_parsed,
+            &BTreeMap::new(),
+            &BTreeMap::new(),
+            &GrowthConstraints::default(),
+        );
 
         if let NextNodeType::Existing(node_id) = existing.next_node {
             assert_eq!(node_id, NodeId::new(1));
@@ -178,9 +218,155 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_junction_rules() {
+        let nodes = [
+            create_node(3.0, 0.0),
+            create_node(1.0, 0.0),
+            create_node(0.0, 1.0),
+            create_node(0.0, 3.0),
+        ];
+
+        let nodes_parsed = nodes
+            .iter()
+            .enumerate()
+            .map(|(i, node)| (node, NodeId::new(i)))
+            .collect::<Vec<_>>();
+
+        let paths = [(0, 1), (1, 2), (2, 3)];
+
+        let paths_parsed = paths
+            .iter()
+            .map(|(start, end)| (nodes_parsed[*start], nodes_parsed[*end]))
+            .collect::<Vec<_>>();
+
+        let neighbor_sites = BTreeMap::from([
+            (NodeId::new(0), vec![nodes[1].site]),
+            (NodeId::new(1), vec![nodes[0].site, nodes[2].site]),
+            (NodeId::new(2), vec![nodes[1].site, nodes[3].site]),
+            (NodeId::new(3), vec![nodes[2].site]),
+        ]);
+
+        let rules = TransportRules::default()
+            .path_normal_length(1.0)
+            .path_extra_length_for_intersection(0.25);
+
+        // junctions along the paths, where the node of the index has more than two paths.
+        let junctions = |junction_index: Option<usize>| {
+            let walked = [0.0, 2.0, 2.0 + 2.0f64.sqrt(), 4.0 + 2.0f64.sqrt()];
+            (0..nodes.len())
+                .map(|i| {
+                    let degree = if Some(i) == junction_index {
+                        3
+                    } else if i == 0 || i == nodes.len() - 1 {
+                        1
+                    } else {
+                        2
+                    };
+                    let nearest_other = junction_index
+                        .filter(|junction_index| *junction_index != i)
+                        .map(|junction_index| (walked[i] - walked[junction_index]).abs())
+                        .unwrap_or(f64::INFINITY);
+                    (
+                        NodeId::new(i),
+                        JunctionDistance {
+                            degree,
+                            nearest_other,
+                        },
+                    )
+                })
+                .collect::<BTreeMap<_, _>>()
+        };
+
+        let node_start = create_node(1.0, 1.0);
+        let determine =
+            |angle: f64,
+             junction_rules: JunctionRules,
+             junction_distances: &BTreeMap<NodeId, JunctionDistance>| {
+                let site_expected_end = node_start
+                    .site
+                    .extend(Angle::new(angle), rules.path_normal_length);
+                Stump::new(
+                    NodeId::new(10000),
+                    TransportNode::new(site_expected_end, 0.0, Stage::default(), false),
+                    rules.clone().junction_rules(junction_rules),
+                    PathMetrics::default(),
+                    0.0,
+                    false,
+                )
+                .determine_growth(
+                    &node_start,
+                    &nodes_parsed,
+                    &paths_parsed,
+                    &neighbor_sites,
+                    junction_distances,
+                    &GrowthConstraints::default(),
+                )
+            };
+
+        // the intersection at (0.5, 0.5) is crossing at right angle.
+        let intersect_angle = -std::f64::consts::PI * 0.25;
+        let intersect = determine(
+            intersect_angle,
+            JunctionRules {
+                min_angle: std::f64::consts::PI / 3.0,
+                min_intersection_spacing: 0.5,
+            },
+            &junctions(None),
+        );
+        assert!(matches!(intersect.next_node, NextNodeType::Intersect(_, _)));
+
+        // the intersection may be close to the nodes of the crossing path which are not junctions.
+        let spacing_rules = |min_intersection_spacing: f64| JunctionRules {
+            min_angle: 0.0,
+            min_intersection_spacing,
+        };
+        let intersect = determine(intersect_angle, spacing_rules(1.0), &junctions(None));
+        assert!(matches!(intersect.next_node, NextNodeType::Intersect(_, _)));
+
+        // the intersection is too close to the junction (1.0, 0.0).
+        let intersect = determine(intersect_angle, spacing_rules(1.0), &junctions(Some(1)));
+        assert!(matches!(intersect.next_node, NextNodeType::None));
+
+        // the intersection is too close to the junction (3.0, 0.0) along the crossing path.
+        let intersect = determine(intersect_angle, spacing_rules(3.0), &junctions(Some(0)));
+        assert!(matches!(intersect.next_node, NextNodeType::None));
+
+        // the path to the existing node (1.0, 0.0) makes an angle of 45 degrees with the path to (0.0, 1.0).
+        let existing_angle = std::f64::consts::PI * 0.05;
+        let existing = determine(
+            existing_angle,
+            JunctionRules {
+                min_angle: std::f64::consts::PI / 6.0,
+                min_intersection_spacing: 0.0,
+            },
+            &junctions(None),
+        );
+        assert!(matches!(existing.next_node, NextNodeType::Existing(_)));
+
+        // neither the existing node nor the intersection beside it is allowed.
+        let existing = determine(
+            existing_angle,
+            JunctionRules {
+                min_angle: std::f64::consts::PI * 0.6,
+                min_intersection_spacing: 0.0,
+            },
+            &junctions(None),
+        );
+        assert!(matches!(existing.next_node, NextNodeType::None));
+
+        // the existing node becomes a junction far enough from the junction (3.0, 0.0).
+        let existing = determine(existing_angle, spacing_rules(1.5), &junctions(Some(0)));
+        assert!(matches!(existing.next_node, NextNodeType::Existing(_)));
+
+        // the existing node becomes a junction too close to the junction (3.0, 0.0).
+        let existing = determine(existing_angle, spacing_rules(2.5), &junctions(Some(0)));
+        assert!(matches!(existing.next_node, NextNodeType::None));
+    }
+
     #[test]
     fn test_next_node_across_multiple_paths() {
-        let nodes = vec![
+        let nodes = [
             create_node(0.0, 0.0),
             create_node(0.3, 0.0),
             create_node(0.7, 0.0),
@@ -197,7 +383,7 @@ mod tests {
             .map(|(i, node)| (node, NodeId::new(i)))
             .collect::<Vec<_>>();
 
-        let paths = vec![(0, 5), (5, 2), (2, 7), (7, 3), (3, 6), (6, 1), (1, 4)];
+        let paths = [(0, 5), (5, 2), (2, 7), (7, 3), (3, 6), (6, 1), (1, 4)];
 
         let paths_parsed = paths
             .iter()
@@ -231,7 +417,14 @@ mod tests {
             0.0,
             false,
         )
-        .determine_growth(&node_start, &nodes_parsed, &paths_parsed);
+        .determine_growth(
+            &node_start,
+            &nodes_parsed,
+            &paths_parsed,
+            &BTreeMap::new(),
+            &BTreeMap::new(),
+            &GrowthConstraints::default(),
+        );
 
         println!("{:?}", next.next_node);
 
@@ -248,7 +441,7 @@ mod tests {
 
     #[test]
     fn test_bridge() {
-        let nodes = vec![
+        let nodes = [
             create_node_detailed(0.0, 0.0, 0.0, false),
             create_node_detailed(1.0, 1.0, 0.0, false),
             create_node_detailed(0.0, 0.0, 1.0, true),
@@ -261,7 +454,7 @@ mod tests {
             .map(|(i, node)| (node, NodeId::new(i)))
             .collect::<Vec<_>>();
 
-        let paths = vec![(0, 1), (2, 3)];
+        let paths = [(0, 1), (2, 3)];
 
         let paths_parsed = paths
             .iter()
@@ -289,7 +482,14 @@ mod tests {
                 0.0,
                 false,
             )
-            .determine_growth(&node_start, &nodes_parsed, &paths_parsed)
+            .determine_growth(
+                &node_start,
+                &nodes_parsed,
+                &paths_parsed,
+                &BTreeMap::new(),
+                &BTreeMap::new(),
+                &GrowthConstraints::default(),
+            )
         };
 
         // New node which passes between two existing paths
@@ -322,4 +522,138 @@ mod tests {
             panic!("Unexpected node type");
         }
     }
+
+    /// Terrain with a hole (no data) around `hole`, which records the number of sites of each query.
+    struct CountingTerrain {
+        hole: Site,
+        queries: RefCell<Vec<usize>>,
+    }
+
+    impl TerrainProvider for CountingTerrain {
+        fn get_elevation(&self, site: &Site) -> Option<f64> {
+            self.get_elevations(&[*site])[0]
+        }
+
+        fn get_elevations(&self, sites: &[Site]) -> Vec<Option<f64>> {
+            self.queries.borrow_mut().push(sites.len());
+            sites
+                .iter()
+                .map(|site| (site.distance(&self.hole) > 0.3).then_some(0.0))
+                .collect()
+        }
+    }
+
+    /// Prioritizator preferring the paths toward `target`, which records the number of paths of each query.
+    struct CountingPrioritizator {
+        target: Site,
+        queries: RefCell<Vec<usize>>,
+    }
+
+    impl PathPrioritizator for CountingPrioritizator {
+        fn prioritize(&self, factors: PathPrioritizationFactors) -> Option<f64> {
+            self.prioritize_many(&[factors])[0]
+        }
+
+        fn prioritize_many(&self, factors: &[PathPrioritizationFactors]) -> Vec<Option<f64>> {
+            self.queries.borrow_mut().push(factors.len());
+            factors
+                .iter()
+                .map(|factors| Some(-factors.site_end.distance(&self.target)))
+                .collect()
+        }
+    }
+
+    /// Create a stump from the origin heading to the straight end at `(1.0, 0.0)`.
+    fn create_stump(
+        terrain: &impl TerrainProvider,
+        prioritizator: &impl PathPrioritizator,
+    ) -> Result<Stump, StumpFailure> {
+        let rules = TransportRules::default()
+            .path_normal_length(1.0)
+            .path_direction_rules(PathDirectionRules {
+                max_radian: 1.0,
+                comparison_step: 3,
+                ..Default::default()
+            })
+            .bridge_rules(BridgeRules {
+                max_bridge_length: 2.0,
+                check_step: 2,
+            });
+
+        let node = create_node(0.0, 0.0);
+        let network = PathNetwork::new();
+        let metrics = PathMetrics::default();
+        let context = GrowthContext {
+            node_id: NodeId::new(0),
+            node: &node,
+            heading: Angle::new(0.0),
+            stage: Stage::default(),
+            metrics: &metrics,
+            degree: 0,
+            nearest_path_distance: None,
+            origin: None,
+            road_density: None,
+            network: &network,
+        };
+
+        Stump::create(
+            terrain,
+            prioritizator,
+            &GrowthConstraints::default(),
+            &[],
+            &[],
+            None,
+            &context,
+            &rules,
+        )
+    }
+
+    #[test]
+    fn test_create_probes_bridges_only_for_blocked_angles() {
+        let straight_end = Site::new(0.0, 0.0).extend(Angle::new(0.0), 1.0);
+        let terrain = CountingTerrain {
+            hole: straight_end,
+            queries: RefCell::new(vec![]),
+        };
+        let prioritizator = CountingPrioritizator {
+            target: straight_end,
+            queries: RefCell::new(vec![]),
+        };
+        let stump = create_stump(&terrain, &prioritizator).unwrap();
+
+        // the straight path falls into the hole, so only the straight angle is probed with bridges.
+        assert_eq!(*terrain.queries.borrow(), vec![1 + 3, 2]);
+        assert_eq!(*prioritizator.queries.borrow(), vec![2, 2]);
+
+        // the curved path is closer to the target than the bridges.
+        assert!(!stump.creates_bridge());
+        assert_eq_f64!(
+            stump
+                .get_node_expected_end()
+                .site
+                .distance(&Site::new(0.0, 0.0)),
+            1.0
+        );
+    }
+
+    #[test]
+    fn test_create_with_cached_providers() {
+        let straight_end = Site::new(0.0, 0.0).extend(Angle::new(0.0), 1.0);
+        let terrain = CachedTerrainProvider::new(CountingTerrain {
+            hole: straight_end,
+            queries: RefCell::new(vec![]),
+        });
+        let prioritizator = CachedPathPrioritizator::new(CountingPrioritizator {
+            target: straight_end,
+            queries: RefCell::new(vec![]),
+        });
+        let stump = create_stump(&terrain, &prioritizator);
+        assert_eq!(create_stump(&terrain, &prioritizator), stump);
+
+        // the second stump is created only from the caches.
+        let terrain = terrain.into_inner();
+        let prioritizator = prioritizator.into_inner();
+        assert_eq!(*terrain.queries.borrow(), vec![1 + 3, 2]);
+        assert_eq!(*prioritizator.queries.borrow(), vec![2, 2]);
+    }
 }
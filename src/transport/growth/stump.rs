@@ -1,22 +1,37 @@
+use std::collections::BTreeMap;
+
 use crate::{
     core::{
         container::path_network::NodeId,
         geometry::{angle::Angle, line_segment::LineSegment, site::Site},
     },
     transport::{
+        attractor::Attractor,
+        constraints::GrowthConstraints,
+        context::GrowthContext,
+        density::RoadDensity,
         node::TransportNode,
         params::{
-            metrics::PathMetrics, numeric::Stage, priority::PathPrioritizationFactors,
-            rules::TransportRules,
+            metrics::{GrowthMetrics, PathMetrics},
+            numeric::Stage,
+            priority::PathPrioritizationFactors,
+            rules::{slope::SlopePath, TransportRules},
         },
         traits::{PathPrioritizator, TerrainProvider},
     },
 };
 
-use super::growth_type::{BridgeNodeType, GrowthTypes, NextNodeType};
+use super::{
+    growth_type::{BridgeNodeType, GrowthTypes, NextNodeType},
+    isoline::{contour_alignment, isoline_deviation},
+    switchback::Switchback,
+};
 
 #[derive(Debug, Clone, PartialEq)]
-pub struct Stump {
+pub struct Stump<M = PathMetrics>
+where
+    M: GrowthMetrics,
+{
     /// node id which this stump is created for.
     node_id: NodeId,
     /// expected end node of the path.
@@ -24,36 +39,119 @@ pub struct Stump {
     /// rules for the path to be created by this stump.
     rules: TransportRules,
     /// metrics for the path to be created by this stump.
-    metrics: PathMetrics,
+    metrics: M,
     /// priority of stump to be dequed.
     priority: f64,
     /// if the path is to be created is a bridge.
     creates_bridge: bool,
+    /// if the path is clipped at the boundary.
+    is_clipped: bool,
+    /// switchback sequence which the path belongs to.
+    switchback: Option<Switchback>,
+    /// order in which the stump is pushed, to deque stumps with the same priority in FIFO order.
+    sequence: u64,
 }
 
-impl Eq for Stump {}
+impl<M> Eq for Stump<M> where M: GrowthMetrics {}
 
-impl PartialOrd for Stump {
+impl<M> PartialOrd for Stump<M>
+where
+    M: GrowthMetrics,
+{
     fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
         Some(self.cmp(other))
     }
 }
 
-impl Ord for Stump {
+impl<M> Ord for Stump<M>
+where
+    M: GrowthMetrics,
+{
     fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        self.priority.total_cmp(&other.priority)
+        self.priority
+            .total_cmp(&other.priority)
+            .then(other.sequence.cmp(&self.sequence))
     }
 }
 
 type RelatedNode<'a> = (&'a TransportNode, NodeId);
 
-impl Stump {
+/// Junctions around a node along the path network, used to keep intersections apart.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct JunctionDistance {
+    /// The number of paths from the node.
+    pub degree: usize,
+    /// Distance along the network to the nearest junction (a node with more than two paths) other than the node.
+    pub nearest_other: f64,
+}
+
+impl JunctionDistance {
+    /// Distance along the network to the nearest junction, which is 0 if the node is a junction.
+    pub fn nearest(&self) -> f64 {
+        if self.degree > 2 {
+            0.0
+        } else {
+            self.nearest_other
+        }
+    }
+}
+
+/// Reason why a stump is not created.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StumpFailure {
+    /// All paths are blocked by the slope limit.
+    ///
+    /// This is the case where the path may climb by a switchback sequence.
+    TooSteep,
+    /// No path is available for the other reasons
+    /// (constraints, clearance, missing elevations or priorities).
+    Blocked,
+}
+
+/// End of the path to be checked by a stump, which is permitted by the constraints.
+struct Probe {
+    /// index of the angle in the search range.
+    angle_index: usize,
+    angle: Angle,
+    site_end: Site,
+    creates_bridge: bool,
+    is_clipped: bool,
+}
+
+/// Candidate of the end of the path to be created by a stump.
+struct Candidate {
+    site_end: Site,
+    elevation_end: f64,
+    /// priority of the path to the candidate.
+    priority: f64,
+    /// score to select the candidate (larger is better).
+    score: f64,
+    /// deviation from the iso-line to follow (smaller is better, prior to `score`).
+    isoline_deviation: f64,
+    creates_bridge: bool,
+    is_clipped: bool,
+}
+
+/// Probes evaluated at once.
+struct Evaluation {
+    /// the first prioritized probe of each angle with the elevation of the end and the priority.
+    candidates: Vec<(Probe, f64, f64)>,
+    /// if any probe has the elevation of the end.
+    has_elevation: bool,
+    /// if any probe is within the slope limit.
+    within_slope: bool,
+}
+
+impl<M> Stump<M>
+where
+    M: GrowthMetrics,
+{
     /// Create a new stump.
     pub(super) fn new(
         node_id: NodeId,
         node_expected_end: TransportNode,
         rules: TransportRules,
-        metrics: PathMetrics,
+        metrics: M,
         priority: f64,
         creates_bridge: bool,
     ) -> Self {
@@ -64,90 +162,418 @@ impl Stump {
             metrics,
             priority,
             creates_bridge,
+            is_clipped: false,
+            switchback: None,
+            sequence: 0,
         }
     }
 
-    /// Create a new stump for the given conditions.
+    /// Set the order in which the stump is pushed.
+    pub fn with_sequence(mut self, sequence: u64) -> Self {
+        self.sequence = sequence;
+        self
+    }
+
+    /// Create a new stump for the path extended in the context.
+    ///
+    /// `nearby_paths` are the existing paths around the node (except the paths from the node),
+    /// which are used to keep the clearance from parallel paths.
+    /// `road_density` is passed to the prioritizator if the builder tracks the road density.
+    /// If no stump is created, the reason is returned.
+    #[allow(clippy::too_many_arguments)]
     pub fn create<TP, PP>(
         terrain_provider: &TP,
         path_prioritizator: &PP,
-        node_tuple: (&TransportNode, NodeId),
-        angle_expected: Angle,
-        stage: Stage,
+        constraints: &GrowthConstraints,
+        attractors: &[Attractor],
+        nearby_paths: &[LineSegment],
+        road_density: Option<&RoadDensity>,
+        context: &GrowthContext<M>,
         rules: &TransportRules,
-        metrics: &PathMetrics,
-    ) -> Option<Self>
+    ) -> Result<Self, StumpFailure>
     where
         TP: TerrainProvider,
-        PP: PathPrioritizator,
+        PP: PathPrioritizator<M>,
     {
-        let (node, node_id) = node_tuple;
+        let (node, node_id) = (context.node, context.node_id);
+        let (angle_expected, stage, metrics) = (context.heading, context.stage, context.metrics);
 
         let path_direction_rules = &rules.path_direction_rules;
-        let (estimated_end_site, creates_bridge) = angle_expected
+        let gradient = if path_direction_rules.contour_affinity != 0.0 {
+            terrain_provider.get_gradient(&node.site, rules.path_normal_length * 0.5)
+        } else {
+            None
+        };
+
+        // Probe of the angle with the bridge of the `i`-th step, if it is permitted geometrically.
+        let probe = |angle_index: usize, angle: Angle, i: usize| {
+            let bridge_path_length = if rules.bridge_rules.check_step == 0 {
+                0.0
+            } else {
+                rules.bridge_rules.max_bridge_length * (i as f64)
+                    / (rules.bridge_rules.check_step as f64)
+            };
+            let site_end_unclipped = node
+                .site
+                .extend(angle, rules.path_normal_length + bridge_path_length);
+            let creates_bridge = i > 0;
+            let site_end = constraints.clip_path(node.site, site_end_unclipped)?;
+            if !constraints.permits_path(node.site, site_end, creates_bridge) {
+                return None;
+            }
+            if !rules
+                .clearance_rules
+                .keeps_clearance(node.site, site_end, nearby_paths)
+            {
+                return None;
+            }
+            Some(Probe {
+                angle_index,
+                angle,
+                site_end,
+                creates_bridge,
+                is_clipped: site_end != site_end_unclipped,
+            })
+        };
+        let angles = angle_expected
             .iter_range_around(
                 path_direction_rules.max_radian,
                 path_direction_rules.comparison_step,
             )
-            .filter_map(|angle| {
-                for i in 0..=rules.bridge_rules.check_step {
-                    let bridge_path_length = if rules.bridge_rules.check_step == 0 {
-                        0.0
-                    } else {
-                        rules.bridge_rules.max_bridge_length * (i as f64)
-                            / (rules.bridge_rules.check_step as f64)
-                    };
-                    let path_length = rules.path_normal_length + bridge_path_length;
-                    let site_end = node.site.extend(angle, path_length);
-                    let creates_bridge = i > 0;
-                    if let Some(priority) =
-                        path_prioritizator.prioritize(PathPrioritizationFactors {
-                            site_start: node.site,
-                            site_end,
-                            path_length,
-                            stage,
-                            creates_bridge,
-                        })
-                    {
-                        if let (Some(elevation_start), Some(elevation_end)) = (
-                            terrain_provider.get_elevation(&node.site),
-                            terrain_provider.get_elevation(&site_end),
-                        ) {
-                            if rules
-                                .path_slope_elevation_diff_limit
-                                .check_slope((elevation_start, elevation_end), path_length)
-                            {
-                                return Some((site_end, priority, creates_bridge));
-                            }
-                        }
-                    }
+            .collect::<Vec<_>>();
+
+        // Query the elevations of the start and the probes without bridges at once.
+        let probes = angles
+            .iter()
+            .enumerate()
+            .filter_map(|(angle_index, angle)| probe(angle_index, *angle, 0))
+            .collect::<Vec<_>>();
+        let sites = std::iter::once(node.site)
+            .chain(probes.iter().map(|probe| probe.site_end))
+            .collect::<Vec<_>>();
+        let mut elevations = terrain_provider.get_elevations(&sites).into_iter();
+        // the elevation of the start is shared by all candidates.
+        let elevation_start = elevations.next().flatten().ok_or(StumpFailure::Blocked)?;
+        let node_start = TransportNode::new(node.site, elevation_start, stage, false);
+
+        let mut evaluation = Self::evaluate_probes(
+            path_prioritizator,
+            road_density,
+            context,
+            rules,
+            &node_start,
+            probes.into_iter().zip(elevations).collect(),
+        );
+
+        // Bridges are only probed for the angles where no path without a bridge is available,
+        // ordered by the bridge length.
+        let bridge_probes = angles
+            .iter()
+            .enumerate()
+            .filter(|(angle_index, _)| {
+                !evaluation
+                    .candidates
+                    .iter()
+                    .any(|(probe, _, _)| probe.angle_index == *angle_index)
+            })
+            .flat_map(|(angle_index, angle)| {
+                (1..=rules.bridge_rules.check_step)
+                    .filter_map(move |i| probe(angle_index, *angle, i))
+            })
+            .collect::<Vec<_>>();
+        if !bridge_probes.is_empty() {
+            let sites = bridge_probes
+                .iter()
+                .map(|probe| probe.site_end)
+                .collect::<Vec<_>>();
+            let elevations = terrain_provider.get_elevations(&sites);
+            let bridge_evaluation = Self::evaluate_probes(
+                path_prioritizator,
+                road_density,
+                context,
+                rules,
+                &node_start,
+                bridge_probes.into_iter().zip(elevations).collect(),
+            );
+            evaluation.candidates.extend(bridge_evaluation.candidates);
+            evaluation
+                .candidates
+                .sort_by_key(|(probe, _, _)| probe.angle_index);
+            evaluation.has_elevation |= bridge_evaluation.has_elevation;
+            evaluation.within_slope |= bridge_evaluation.within_slope;
+        }
+        // the path is too steep only if the slope limit rejects all probes which have elevations.
+        let failure = if evaluation.has_elevation && !evaluation.within_slope {
+            StumpFailure::TooSteep
+        } else {
+            StumpFailure::Blocked
+        };
+
+        let candidate = evaluation
+            .candidates
+            .into_iter()
+            .map(|(probe, elevation_end, priority)| {
+                let Probe {
+                    angle,
+                    site_end,
+                    creates_bridge,
+                    is_clipped,
+                    ..
+                } = probe;
+                // attractors bias the selection of the candidate,
+                // but don't change the priority of the stump.
+                let bias = attractors
+                    .iter()
+                    .map(|attractor| attractor.bias(&node.site, angle))
+                    .sum::<f64>();
+                let contour_score = gradient.map_or(0.0, |gradient| {
+                    path_direction_rules.contour_affinity * contour_alignment(gradient, angle)
+                });
+                // if the path follows an iso-line, the deviation from the iso-line is prior to the score.
+                let isoline_deviation = if rules.isoline_rules.target.is_some() {
+                    isoline_deviation(terrain_provider, &rules.isoline_rules, &site_end, angle)
+                        .unwrap_or(f64::INFINITY)
+                } else {
+                    0.0
+                };
+                Candidate {
+                    site_end,
+                    elevation_end,
+                    priority,
+                    score: priority + bias + contour_score,
+                    isoline_deviation,
+                    creates_bridge,
+                    is_clipped,
                 }
-                None
             })
-            .max_by(|(_, ev0, _), (_, ev1, _)| ev0.total_cmp(ev1))
-            .map(|(site, _, creates_bridge)| (site, creates_bridge))?;
-
-        let priority = path_prioritizator.prioritize(PathPrioritizationFactors {
-            site_start: node.site,
-            site_end: estimated_end_site,
-            path_length: rules.path_normal_length,
-            stage,
-            creates_bridge,
-        })?;
+            .max_by(|c0, c1| {
+                c1.isoline_deviation
+                    .total_cmp(&c0.isoline_deviation)
+                    .then(c0.score.total_cmp(&c1.score))
+            })
+            .ok_or(failure)?;
+        let creates_bridge = candidate.creates_bridge;
 
-        Some(Self::new(
-            node_id,
-            TransportNode::new(
-                estimated_end_site,
-                terrain_provider.get_elevation(&estimated_end_site)?,
-                stage,
+        // Snap to the closest attractor around the path.
+        let snapped_end = attractors
+            .iter()
+            .filter(|attractor| {
+                attractor.snaps(
+                    &LineSegment::new(node.site, candidate.site_end),
+                    rules.path_extra_length_for_intersection,
+                )
+            })
+            .filter(|attractor| constraints.permits_path(node.site, attractor.site, creates_bridge))
+            .filter_map(|attractor| {
+                let elevation_end = terrain_provider.get_elevation(&attractor.site)?;
+                rules
+                    .path_slope_elevation_diff_limit
+                    .check_slope(
+                        &SlopePath::new(
+                            node_start,
+                            TransportNode::new(
+                                attractor.site,
+                                elevation_end,
+                                stage,
+                                creates_bridge,
+                            ),
+                        )
+                        .rules(rules),
+                    )
+                    .then_some((attractor.site, elevation_end))
+            })
+            .min_by(|(a, _), (b, _)| {
+                a.distance_2(&node.site)
+                    .total_cmp(&b.distance_2(&node.site))
+            });
+        let (estimated_end_site, elevation_end, is_clipped) =
+            if let Some((site, elevation)) = snapped_end {
+                (site, elevation, false)
+            } else {
+                (
+                    candidate.site_end,
+                    candidate.elevation_end,
+                    candidate.is_clipped,
+                )
+            };
+
+        // the priority of the candidate is reused if the path is not changed by the snapping.
+        let priority = if snapped_end.is_none() && !creates_bridge && !candidate.is_clipped {
+            candidate.priority
+        } else {
+            path_prioritizator
+                .prioritize(PathPrioritizationFactors {
+                    site_start: node.site,
+                    site_end: estimated_end_site,
+                    path_length: rules.path_normal_length,
+                    stage,
+                    creates_bridge,
+                    road_density: road_density
+                        .map(|density| density.get_density(&estimated_end_site)),
+                    context,
+                })
+                .ok_or(StumpFailure::Blocked)?
+        };
+
+        Ok(Self {
+            is_clipped,
+            ..Self::new(
+                node_id,
+                TransportNode::new(estimated_end_site, elevation_end, stage, false),
+                rules.clone(),
+                metrics.clone(),
+                priority,
+                creates_bridge,
+            )
+        })
+    }
+
+    /// Evaluate the probes with their elevations at once.
+    ///
+    /// The probes within the slope limit are prioritized at once,
+    /// and the first prioritized probe of each angle (the shortest bridge) is the candidate of the angle.
+    fn evaluate_probes<PP>(
+        path_prioritizator: &PP,
+        road_density: Option<&RoadDensity>,
+        context: &GrowthContext<M>,
+        rules: &TransportRules,
+        node_start: &TransportNode,
+        probes: Vec<(Probe, Option<f64>)>,
+    ) -> Evaluation
+    where
+        PP: PathPrioritizator<M>,
+    {
+        let probes = probes
+            .into_iter()
+            .filter_map(|(probe, elevation_end)| Some((probe, elevation_end?)))
+            .collect::<Vec<_>>();
+        let has_elevation = !probes.is_empty();
+        let probes = probes
+            .into_iter()
+            .filter(|(probe, elevation_end)| {
+                rules.path_slope_elevation_diff_limit.check_slope(
+                    &SlopePath::new(
+                        *node_start,
+                        TransportNode::new(
+                            probe.site_end,
+                            *elevation_end,
+                            node_start.stage,
+                            probe.creates_bridge,
+                        ),
+                    )
+                    .rules(rules),
+                )
+            })
+            .collect::<Vec<_>>();
+        if probes.is_empty() {
+            return Evaluation {
+                candidates: vec![],
+                has_elevation,
+                within_slope: false,
+            };
+        }
+
+        let factors = probes
+            .iter()
+            .map(|(probe, _)| PathPrioritizationFactors {
+                site_start: node_start.site,
+                site_end: probe.site_end,
+                path_length: node_start.site.distance(&probe.site_end),
+                stage: node_start.stage,
+                creates_bridge: probe.creates_bridge,
+                road_density: road_density.map(|density| density.get_density(&probe.site_end)),
+                context,
+            })
+            .collect::<Vec<_>>();
+        let priorities = path_prioritizator.prioritize_many(&factors);
+
+        let mut last_angle_index = None;
+        let candidates = probes
+            .into_iter()
+            .zip(priorities)
+            .filter_map(|((probe, elevation_end), priority)| {
+                if last_angle_index == Some(probe.angle_index) {
+                    return None;
+                }
+                let priority = priority?;
+                last_angle_index = Some(probe.angle_index);
+                Some((probe, elevation_end, priority))
+            })
+            .collect();
+        Evaluation {
+            candidates,
+            has_elevation,
+            within_slope: true,
+        }
+    }
+
+    /// Create a new stump for a leg of the switchback sequence.
+    ///
+    /// The direction of the leg is searched from the heading of the sequence to the contour
+    /// derived from the terrain gradient, and the steepest one within the slope limit is selected.
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_switchback<TP, PP>(
+        terrain_provider: &TP,
+        path_prioritizator: &PP,
+        constraints: &GrowthConstraints,
+        road_density: Option<&RoadDensity>,
+        context: &GrowthContext<M>,
+        switchback: &Switchback,
+        rules: &TransportRules,
+    ) -> Option<Self>
+    where
+        TP: TerrainProvider,
+        PP: PathPrioritizator<M>,
+    {
+        let (node, node_id) = (context.node, context.node_id);
+        let (stage, metrics) = (context.stage, context.metrics);
+        if rules.switchback_rules.max_hairpins == 0 {
+            return None;
+        }
+
+        let elevation_start = terrain_provider.get_elevation(&node.site)?;
+        let gradient = terrain_provider.get_gradient(&node.site, rules.path_normal_length * 0.5);
+        let (site_end, elevation_end, priority, clockwise) = switchback
+            .leg_angles(&rules.switchback_rules, gradient)
+            .into_iter()
+            .find_map(|(angle, clockwise)| {
+                let site_end = node.site.extend(angle, rules.path_normal_length);
+                if !constraints.permits_path(node.site, site_end, false) {
+                    return None;
+                }
+                let elevation_end = terrain_provider.get_elevation(&site_end)?;
+                if !rules.path_slope_elevation_diff_limit.check_slope(
+                    &SlopePath::new(
+                        TransportNode::new(node.site, elevation_start, stage, false),
+                        TransportNode::new(site_end, elevation_end, stage, false),
+                    )
+                    .rules(rules),
+                ) {
+                    return None;
+                }
+                let priority = path_prioritizator.prioritize(PathPrioritizationFactors {
+                    site_start: node.site,
+                    site_end,
+                    path_length: rules.path_normal_length,
+                    stage,
+                    creates_bridge: false,
+                    road_density: road_density.map(|density| density.get_density(&site_end)),
+                    context,
+                })?;
+                Some((site_end, elevation_end, priority, clockwise))
+            })?;
+
+        Some(Self {
+            switchback: Some(switchback.on_side(clockwise)),
+            ..Self::new(
+                node_id,
+                TransportNode::new(site_end, elevation_end, stage, false),
+                rules.clone(),
+                metrics.clone(),
+                priority,
                 false,
-            ),
-            rules.clone(),
-            metrics.clone(),
-            priority,
-            creates_bridge,
-        ))
+            )
+        })
     }
 
     pub fn get_node_id(&self) -> NodeId {
@@ -162,14 +588,43 @@ impl Stump {
         &self.rules
     }
 
-    pub fn get_metrics(&self) -> &PathMetrics {
+    pub fn get_metrics(&self) -> &M {
         &self.metrics
     }
 
+    pub fn creates_bridge(&self) -> bool {
+        self.creates_bridge
+    }
+
     pub fn get_stage(&self) -> Stage {
         self.node_expected_end.stage
     }
 
+    pub fn is_clipped(&self) -> bool {
+        self.is_clipped
+    }
+
+    pub fn get_switchback(&self) -> Option<&Switchback> {
+        self.switchback.as_ref()
+    }
+
+    /// Get the bounding box `(min, max)` of the region searched for the growth of the stump.
+    #[cfg(feature = "rayon")]
+    pub fn search_bounds(&self, start_site: Site) -> (Site, Site) {
+        let end_site = self.node_expected_end.site;
+        let margin = self.rules.path_extra_length_for_intersection;
+        (
+            Site::new(
+                start_site.x.min(end_site.x) - margin,
+                start_site.y.min(end_site.y) - margin,
+            ),
+            Site::new(
+                start_site.x.max(end_site.x) + margin,
+                start_site.y.max(end_site.y) + margin,
+            ),
+        )
+    }
+
     /// Get the end site of the path with extra length.
     /// This is temporary used for searching intersections.
     fn get_expected_site_to_with_extra_length(
@@ -211,21 +666,49 @@ impl Stump {
     fn check_slope(&self, node0: &TransportNode, node1: &TransportNode) -> bool {
         // slope check
         // if the elevation difference is too large, the path cannot be connected.
-        let distance = node0.site.distance(&node1.site);
         self.rules
             .path_slope_elevation_diff_limit
-            .check_slope((node0.elevation, node1.elevation), distance)
+            .check_slope(&SlopePath::new(*node0, *node1).rules(&self.rules))
+    }
+
+    /// Check if the path from `site` to `site_to` keeps the minimum angle from the paths from `site` to `sites_other`.
+    fn keeps_min_angle(&self, site: Site, site_to: Site, sites_other: &[Site]) -> bool {
+        let min_angle = self.rules.junction_rules.min_angle;
+        if min_angle <= 0.0 {
+            return true;
+        }
+        let angle = site.get_angle(&site_to);
+        sites_other
+            .iter()
+            .all(|site_other| angle.diff(&site.get_angle(site_other)) >= min_angle)
     }
 
     /// Determine the next node type from related(close) nodes and paths.
+    ///
+    /// `neighbor_sites` contains the sites of the nodes adjacent to the start node and the related nodes,
+    /// which are used to check the angles between paths. Nodes not in the map are not checked.
+    ///
+    /// `junction_distances` contains the junctions around the related nodes and the nodes of the related paths,
+    /// which are used to check the spacing between intersections. Nodes not in the map are not checked.
     pub fn determine_growth(
         &self,
         node_start: &TransportNode,
         related_nodes: &[RelatedNode],
         related_paths: &[(RelatedNode, RelatedNode)],
+        neighbor_sites: &BTreeMap<NodeId, Vec<Site>>,
+        junction_distances: &BTreeMap<NodeId, JunctionDistance>,
+        constraints: &GrowthConstraints,
     ) -> GrowthTypes {
         let search_start = node_start.site;
         let node_expected_end = &self.node_expected_end;
+        let neighbors_of = |node_id: NodeId| {
+            neighbor_sites
+                .get(&node_id)
+                .map(|sites| sites.as_slice())
+                .unwrap_or_default()
+        };
+        let start_neighbors = neighbors_of(self.node_id);
+        let spacing = self.rules.junction_rules.min_intersection_spacing;
 
         // Existing Node
         // For this situation, path crosses are needed to be checked again because the direction of the path can be changed from original.
@@ -243,6 +726,11 @@ impl Stump {
                     // if the existing node is creates_bridge, the path cannot be connected.
                     !existing_node.is_bridge
                 })
+                .filter(|(existing_node, _)| {
+                    // constraints check
+                    // the path to the existing node should not leave the boundary or enter keep-out zones.
+                    constraints.permits_path(search_start, existing_node.site, self.creates_bridge)
+                })
                 .filter(|(existing_node, existing_node_id)| {
                     // no intersection check
                     Self::get_crossing(
@@ -264,6 +752,25 @@ impl Stump {
                         == 0
                 })
                 .filter(|(existing_node, _)| self.check_slope(node_start, existing_node))
+                .filter(|(_, existing_node_id)| {
+                    // spacing check
+                    // the existing node should not become a junction close to the other junctions.
+                    junction_distances
+                        .get(existing_node_id)
+                        .is_none_or(|junction| {
+                            junction.degree != 2 || junction.nearest_other >= spacing
+                        })
+                })
+                .filter(|(existing_node, existing_node_id)| {
+                    // angle check
+                    // the path should not make acute angles with the other paths at both ends.
+                    self.keeps_min_angle(search_start, existing_node.site, start_neighbors)
+                        && self.keeps_min_angle(
+                            existing_node.site,
+                            search_start,
+                            neighbors_of(*existing_node_id),
+                        )
+                })
                 .min_by(|a, b: &&(&TransportNode, NodeId)| {
                     let distance_a = a.0.site.distance_2(&search_start);
                     let distance_b = b.0.site.distance_2(&search_start);
@@ -313,6 +820,10 @@ impl Stump {
                     // check slope
                     self.check_slope(node_start, crossing_node)
                 })
+                .filter(|(crossing_node, _)| {
+                    // the search line may be extended beyond the boundary.
+                    constraints.permits_path(search_start, crossing_node.site, self.creates_bridge)
+                })
                 .min_by(|a, b| {
                     let distance_a = a.0.site.distance_2(&search_start);
                     let distance_b = b.0.site.distance_2(&search_start);
@@ -327,6 +838,25 @@ impl Stump {
                         bridge_node: BridgeNodeType::None,
                     };
                 }
+                // if the intersection is too close to the other junctions along the path or makes acute angles,
+                // the path cannot be connected.
+                let path_sites = [path_nodes.0 .0.site, path_nodes.1 .0.site];
+                let nearest_junction = [path_nodes.0, path_nodes.1]
+                    .iter()
+                    .filter_map(|(path_node, path_node_id)| {
+                        let junction = junction_distances.get(path_node_id)?;
+                        Some(path_node.site.distance(&crossing_node.site) + junction.nearest())
+                    })
+                    .fold(f64::INFINITY, f64::min);
+                if nearest_junction < spacing
+                    || !self.keeps_min_angle(search_start, crossing_node.site, start_neighbors)
+                    || !self.keeps_min_angle(crossing_node.site, search_start, &path_sites)
+                {
+                    return GrowthTypes {
+                        next_node: NextNodeType::None,
+                        bridge_node: BridgeNodeType::None,
+                    };
+                }
                 let middle = if self.creates_bridge {
                     let middle_site = search_start.midpoint(&crossing_node.site);
                     BridgeNodeType::Middle(TransportNode::new(
@@ -359,8 +889,10 @@ impl Stump {
             }
         }
 
-        // check slope
-        if !self.check_slope(node_start, node_expected_end) {
+        // check slope and angle
+        if !self.check_slope(node_start, node_expected_end)
+            || !self.keeps_min_angle(search_start, node_expected_end.site, start_neighbors)
+        {
             return GrowthTypes {
                 next_node: NextNodeType::None,
                 bridge_node: BridgeNodeType::None,
@@ -0,0 +1,188 @@
+use crate::{core::geometry::angle::Angle, transport::params::rules::switchback::SwitchbackRules};
+
+/// State of a switchback sequence.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Switchback {
+    /// The direction which the path originally heads for.
+    heading: Angle,
+    /// The side of the current leg from the heading.
+    /// If `None`, the sequence is not started yet and both sides are candidates.
+    clockwise: Option<bool>,
+    /// Number of hairpins created in the sequence.
+    hairpins: usize,
+    /// Length of the current leg.
+    leg_length: f64,
+}
+
+impl Switchback {
+    /// Create a new switchback sequence for the blocked direction.
+    pub fn new(heading: Angle) -> Self {
+        Self {
+            heading,
+            clockwise: None,
+            hairpins: 0,
+            leg_length: 0.0,
+        }
+    }
+
+    /// Get the direction which the path originally heads for.
+    pub fn heading(&self) -> Angle {
+        self.heading
+    }
+
+    /// Get the state whose current leg is on the specified side.
+    pub fn on_side(&self, clockwise: bool) -> Self {
+        Self {
+            clockwise: Some(clockwise),
+            ..self.clone()
+        }
+    }
+
+    /// Candidate angles of the current leg with their sides.
+    ///
+    /// The candidates are ordered from the closest to the heading (steepest) to the contour.
+    /// The contour is derived from the terrain `gradient` at the start of the leg,
+    /// or assumed to be perpendicular to the heading if the gradient is not available.
+    /// The contour itself is excluded because the leg along it gains no elevation.
+    pub fn leg_angles(
+        &self,
+        rules: &SwitchbackRules,
+        gradient: Option<(f64, f64)>,
+    ) -> Vec<(Angle, bool)> {
+        let sides = match self.clockwise {
+            Some(clockwise) => vec![clockwise],
+            None => vec![true, false],
+        };
+        let (offset_clockwise, offset_counterclockwise) = self.contour_offsets(gradient);
+        (1..=rules.check_step)
+            .flat_map(|i| {
+                let ratio = (i as f64) / ((rules.check_step + 1) as f64);
+                sides.iter().map(move |clockwise| {
+                    let offset = if *clockwise {
+                        offset_clockwise
+                    } else {
+                        offset_counterclockwise
+                    };
+                    (
+                        Angle::new(self.heading.radian() + offset * ratio),
+                        *clockwise,
+                    )
+                })
+            })
+            .collect()
+    }
+
+    /// Get the radian from the heading to the contour on the clockwise and the counterclockwise sides.
+    fn contour_offsets(&self, gradient: Option<(f64, f64)>) -> (f64, f64) {
+        let offset_clockwise = gradient.filter(|(dx, dy)| *dx != 0.0 || *dy != 0.0).map_or(
+            0.5 * std::f64::consts::PI,
+            |(dx, dy)| {
+                // the elevation doesn't change along the angle perpendicular to the gradient.
+                let offset = Angle::new(dy.atan2(dx) - self.heading.radian()).radian();
+                if offset > 0.0 {
+                    offset
+                } else {
+                    offset + std::f64::consts::PI
+                }
+            },
+        );
+        (offset_clockwise, offset_clockwise - std::f64::consts::PI)
+    }
+
+    /// Get the state after the current leg is extended by `length`.
+    ///
+    /// If the leg is long enough, a hairpin is created and the next leg turns to the opposite side.
+    /// If the number of hairpins exceeds the limit, return None.
+    pub fn extended(&self, length: f64, rules: &SwitchbackRules) -> Option<Self> {
+        let leg_length = self.leg_length + length;
+        if leg_length < rules.min_hairpin_spacing {
+            return Some(Self {
+                leg_length,
+                ..self.clone()
+            });
+        }
+        if self.hairpins >= rules.max_hairpins {
+            return None;
+        }
+        Some(Self {
+            heading: self.heading,
+            clockwise: self.clockwise.map(|clockwise| !clockwise),
+            hairpins: self.hairpins + 1,
+            leg_length: 0.0,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_leg_angles() {
+        let rules = SwitchbackRules {
+            max_hairpins: 2,
+            min_hairpin_spacing: 1.0,
+            check_step: 2,
+        };
+        let switchback = Switchback::new(Angle::new(0.0));
+
+        let angles = switchback.leg_angles(&rules, None);
+        assert_eq!(angles.len(), 4);
+        assert_eq!(angles[0], (Angle::new(std::f64::consts::PI / 6.0), true));
+        assert_eq!(angles[1], (Angle::new(-std::f64::consts::PI / 6.0), false));
+        assert_eq!(angles[3], (Angle::new(-std::f64::consts::PI / 3.0), false));
+
+        let angles = switchback.on_side(false).leg_angles(&rules, None);
+        assert_eq!(
+            angles,
+            vec![
+                (Angle::new(-std::f64::consts::PI / 6.0), false),
+                (Angle::new(-std::f64::consts::PI / 3.0), false)
+            ]
+        );
+
+        // the contours are skewed from the heading on the slope rising toward +x and -y.
+        let angles = switchback.leg_angles(&rules, Some((1.0, -1.0)));
+        let expected = [
+            (std::f64::consts::PI / 4.0, true),
+            (-std::f64::consts::PI / 12.0, false),
+            (std::f64::consts::PI / 2.0, true),
+            (-std::f64::consts::PI / 6.0, false),
+        ];
+        for ((angle, clockwise), (radian, expected_clockwise)) in angles.iter().zip(expected) {
+            assert!((angle.radian() - radian).abs() < 1e-9);
+            assert_eq!(*clockwise, expected_clockwise);
+        }
+        // the legs along the contours gain no elevation.
+        let (offset_clockwise, offset_counterclockwise) =
+            switchback.contour_offsets(Some((1.0, -1.0)));
+        for offset in [offset_clockwise, offset_counterclockwise] {
+            let angle = Angle::new(offset);
+            assert!((angle.unit_x() - angle.unit_y()).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_extended() {
+        let rules = SwitchbackRules {
+            max_hairpins: 1,
+            min_hairpin_spacing: 1.0,
+            check_step: 2,
+        };
+        let switchback = Switchback::new(Angle::new(0.0)).on_side(true);
+
+        // the leg continues
+        let switchback = switchback.extended(0.6, &rules).unwrap();
+        assert_eq!(switchback.hairpins, 0);
+        assert_eq!(switchback.clockwise, Some(true));
+
+        // hairpin
+        let switchback = switchback.extended(0.6, &rules).unwrap();
+        assert_eq!(switchback.hairpins, 1);
+        assert_eq!(switchback.clockwise, Some(false));
+
+        // exceeds the limit of hairpins
+        let switchback = switchback.extended(1.0, &rules);
+        assert!(switchback.is_none());
+    }
+}
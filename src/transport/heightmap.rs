@@ -0,0 +1,412 @@
+use crate::core::geometry::site::Site;
+
+use super::traits::TerrainProvider;
+
+/// Interpolation of the elevations between the grid points.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Interpolation {
+    /// Interpolate from the 2x2 grid points.
+    #[default]
+    Bilinear,
+    /// Interpolate from the 4x4 grid points with Catmull-Rom splines.
+    Bicubic,
+}
+
+impl Interpolation {
+    /// Number of the grid points on each axis used for the interpolation.
+    fn size(&self) -> usize {
+        match self {
+            Interpolation::Bilinear => 2,
+            Interpolation::Bicubic => 4,
+        }
+    }
+
+    /// Get the weights of the grid points and their derivatives at the fraction `t` in `[0, 1)`.
+    fn weights(&self, t: f64) -> ([f64; 4], [f64; 4]) {
+        match self {
+            Interpolation::Bilinear => ([1.0 - t, t, 0.0, 0.0], [-1.0, 1.0, 0.0, 0.0]),
+            Interpolation::Bicubic => {
+                let (t2, t3) = (t * t, t * t * t);
+                (
+                    [
+                        0.5 * (-t3 + 2.0 * t2 - t),
+                        0.5 * (3.0 * t3 - 5.0 * t2 + 2.0),
+                        0.5 * (-3.0 * t3 + 4.0 * t2 + t),
+                        0.5 * (t3 - t2),
+                    ],
+                    [
+                        0.5 * (-3.0 * t2 + 4.0 * t - 1.0),
+                        0.5 * (9.0 * t2 - 10.0 * t),
+                        0.5 * (-9.0 * t2 + 8.0 * t + 1.0),
+                        0.5 * (3.0 * t2 - 2.0 * t),
+                    ],
+                )
+            }
+        }
+    }
+}
+
+/// Terrain provider from a raster grid of elevations (heightmap).
+///
+/// The grid point `(i, j)` (column `i`, row `j`) is placed at `origin + (i, j) * cell_size`.
+/// The elevation is `None` outside of the grid, around "no-data" grid points, and below the sea level.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Heightmap {
+    width: usize,
+    height: usize,
+    elevations: Vec<f32>,
+    origin: Site,
+    cell_size: f64,
+    elevation_scale: f64,
+    elevation_offset: f64,
+    interpolation: Interpolation,
+    no_data: Option<f32>,
+    sea_level: Option<f64>,
+}
+
+impl Heightmap {
+    /// Create a heightmap from the elevations in row-major order.
+    ///
+    /// If the number of elevations doesn't match the size (or the size overflows), `None` is returned.
+    pub fn new(width: usize, height: usize, elevations: Vec<f32>) -> Option<Self> {
+        if width == 0 || height == 0 || width.checked_mul(height) != Some(elevations.len()) {
+            return None;
+        }
+        Some(Self {
+            width,
+            height,
+            elevations,
+            origin: Site::new(0.0, 0.0),
+            cell_size: 1.0,
+            elevation_scale: 1.0,
+            elevation_offset: 0.0,
+            interpolation: Interpolation::default(),
+            no_data: None,
+            sea_level: None,
+        })
+    }
+
+    /// Create a heightmap from raw little-endian `f32` values in row-major order.
+    pub fn from_raw_f32_le(width: usize, height: usize, bytes: &[u8]) -> Option<Self> {
+        if !bytes.len().is_multiple_of(4) {
+            return None;
+        }
+        let elevations = bytes
+            .chunks_exact(4)
+            .map(|chunk| f32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]))
+            .collect::<Vec<_>>();
+        Self::new(width, height, elevations)
+    }
+
+    /// Create a heightmap from a binary PGM (P5) image, 8-bit or 16-bit (big-endian).
+    ///
+    /// The elevations are the raw values of the pixels, which are usually scaled by `elevation_scale`.
+    /// Bytes after the pixels (e.g. the following images of the file) are ignored.
+    pub fn from_pgm(bytes: &[u8]) -> Option<Self> {
+        let mut position = 0;
+        let mut header = [0; 4];
+        for (index, value) in header.iter_mut().enumerate() {
+            // skip whitespaces and comments.
+            loop {
+                match bytes.get(position)? {
+                    b'#' => {
+                        while *bytes.get(position)? != b'\n' {
+                            position += 1;
+                        }
+                    }
+                    byte if byte.is_ascii_whitespace() => position += 1,
+                    _ => break,
+                }
+            }
+            let start = position;
+            while bytes
+                .get(position)
+                .is_some_and(|byte| !byte.is_ascii_whitespace())
+            {
+                position += 1;
+            }
+            let token = std::str::from_utf8(&bytes[start..position]).ok()?;
+            if index == 0 {
+                if token != "P5" {
+                    return None;
+                }
+            } else {
+                *value = token.parse::<usize>().ok()?;
+            }
+        }
+        let [_, width, height, max_value] = header;
+        if max_value == 0 || max_value > u16::MAX as usize {
+            return None;
+        }
+        // a single whitespace separates the header and the pixels.
+        let sample_size = if max_value < 256 { 1 } else { 2 };
+        let pixels_size = width.checked_mul(height)?.checked_mul(sample_size)?;
+        let pixels = bytes.get(position + 1..)?.get(..pixels_size)?;
+
+        let elevations = if sample_size == 1 {
+            pixels.iter().map(|pixel| *pixel as f32).collect::<Vec<_>>()
+        } else {
+            pixels
+                .chunks_exact(2)
+                .map(|chunk| u16::from_be_bytes([chunk[0], chunk[1]]) as f32)
+                .collect::<Vec<_>>()
+        };
+        Self::new(width, height, elevations)
+    }
+
+    /// Set the world-space position of the grid point `(0, 0)` and the distance between grid points.
+    pub fn transform(mut self, origin: Site, cell_size: f64) -> Self {
+        self.origin = origin;
+        self.cell_size = cell_size;
+        self
+    }
+
+    /// Set the conversion from the values of the grid to elevations (`value * scale + offset`).
+    pub fn elevation_scale(mut self, scale: f64, offset: f64) -> Self {
+        self.elevation_scale = scale;
+        self.elevation_offset = offset;
+        self
+    }
+
+    /// Set the interpolation.
+    pub fn interpolation(mut self, interpolation: Interpolation) -> Self {
+        self.interpolation = interpolation;
+        self
+    }
+
+    /// Set the value of the grid which means no data.
+    pub fn no_data(mut self, no_data: f32) -> Self {
+        self.no_data = Some(no_data);
+        self
+    }
+
+    /// Set the sea level, below which the elevation is `None`.
+    pub fn sea_level(mut self, sea_level: f64) -> Self {
+        self.sea_level = Some(sea_level);
+        self
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Get the elevation of the grid point, or `None` if the point has no data.
+    ///
+    /// Points outside of the grid are extrapolated linearly from the edge,
+    /// so that the interpolation of a plane is exact at the edges.
+    fn value_at(&self, i: isize, j: isize) -> Option<f64> {
+        let (width, height) = (self.width as isize, self.height as isize);
+        if i < 0 || i >= width {
+            let (edge, inner) = if i < 0 {
+                (0, 1)
+            } else {
+                (width - 1, width - 2)
+            };
+            if inner < 0 || inner >= width {
+                return self.value_at(edge, j);
+            }
+            return Some(2.0 * self.value_at(edge, j)? - self.value_at(inner, j)?);
+        }
+        if j < 0 || j >= height {
+            let (edge, inner) = if j < 0 {
+                (0, 1)
+            } else {
+                (height - 1, height - 2)
+            };
+            if inner < 0 || inner >= height {
+                return self.value_at(i, edge);
+            }
+            return Some(2.0 * self.value_at(i, edge)? - self.value_at(i, inner)?);
+        }
+
+        let value = self.elevations[(j * width + i) as usize];
+        if value.is_nan() || self.no_data == Some(value) {
+            return None;
+        }
+        Some(value as f64 * self.elevation_scale + self.elevation_offset)
+    }
+
+    /// Interpolate the elevation and its gradient `(dz/dx, dz/dy)` at the site.
+    fn interpolate(&self, site: &Site, with_gradient: bool) -> Option<(f64, f64, f64)> {
+        let gx = (site.x - self.origin.x) / self.cell_size;
+        let gy = (site.y - self.origin.y) / self.cell_size;
+        let (max_x, max_y) = ((self.width - 1) as f64, (self.height - 1) as f64);
+        if !(0.0..=max_x).contains(&gx) || !(0.0..=max_y).contains(&gy) {
+            return None;
+        }
+
+        // the last grid point is regarded as the end of the previous cell.
+        let (cell_x, cell_y) = (
+            (gx.floor() as usize).min(self.width.saturating_sub(2)),
+            (gy.floor() as usize).min(self.height.saturating_sub(2)),
+        );
+        let (tx, ty) = (gx - cell_x as f64, gy - cell_y as f64);
+        let (wx, dwx) = self.interpolation.weights(tx);
+        let (wy, dwy) = self.interpolation.weights(ty);
+
+        let size = self.interpolation.size();
+        // the stencil starts before the cell for bicubic interpolation.
+        let start_offset = (size / 2 - 1) as isize;
+        // grid points without any weight are skipped, so that the grid points next to
+        // no-data points can be evaluated exactly.
+        let is_used = |w: f64, dw: f64| w != 0.0 || (with_gradient && dw != 0.0);
+
+        let (mut value, mut gradient_x, mut gradient_y) = (0.0, 0.0, 0.0);
+        for ky in 0..size {
+            if !is_used(wy[ky], dwy[ky]) {
+                continue;
+            }
+            let j = cell_y as isize + ky as isize - start_offset;
+            let (mut row, mut row_derivative) = (0.0, 0.0);
+            for kx in 0..size {
+                if !is_used(wx[kx], dwx[kx]) {
+                    continue;
+                }
+                let i = cell_x as isize + kx as isize - start_offset;
+                let sample = self.value_at(i, j)?;
+                row += wx[kx] * sample;
+                row_derivative += dwx[kx] * sample;
+            }
+            value += wy[ky] * row;
+            gradient_x += wy[ky] * row_derivative;
+            gradient_y += dwy[ky] * row;
+        }
+
+        if self.sea_level.is_some_and(|sea_level| value < sea_level) {
+            return None;
+        }
+        Some((
+            value,
+            gradient_x / self.cell_size,
+            gradient_y / self.cell_size,
+        ))
+    }
+}
+
+impl TerrainProvider for Heightmap {
+    fn get_elevation(&self, site: &Site) -> Option<f64> {
+        self.interpolate(site, false)
+            .map(|(elevation, _, _)| elevation)
+    }
+
+    /// The gradient is calculated analytically from the interpolation, so `delta` is not used.
+    fn get_gradient(&self, site: &Site, _: f64) -> Option<(f64, f64)> {
+        self.interpolate(site, true)
+            .map(|(_, gradient_x, gradient_y)| (gradient_x, gradient_y))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Create a heightmap of the plane `z = 2x + 3y` on the grid of the size.
+    fn create_plane(width: usize, height: usize, cell_size: f64) -> Heightmap {
+        let elevations = (0..height)
+            .flat_map(|j| (0..width).map(move |i| (2 * i + 3 * j) as f32))
+            .collect::<Vec<_>>();
+        Heightmap::new(width, height, elevations)
+            .unwrap()
+            .transform(Site::new(-1.0, -1.0), cell_size)
+            .elevation_scale(cell_size, 0.0)
+    }
+
+    fn assert_close(actual: Option<f64>, expected: f64) {
+        let actual = actual.unwrap();
+        assert!(
+            (actual - expected).abs() < 1e-9,
+            "{} != {}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn test_interpolation_on_plane() {
+        for interpolation in [Interpolation::Bilinear, Interpolation::Bicubic] {
+            let heightmap = create_plane(5, 4, 0.5).interpolation(interpolation);
+            for site in [
+                Site::new(-1.0, -1.0),
+                Site::new(0.3, 0.2),
+                Site::new(-0.9, 0.4),
+                Site::new(1.0, 0.5),
+            ] {
+                let expected = 2.0 * (site.x + 1.0) + 3.0 * (site.y + 1.0);
+                assert_close(heightmap.get_elevation(&site), expected);
+                let gradient = heightmap.get_gradient(&site, 0.0);
+                assert_close(gradient.map(|gradient| gradient.0), 2.0);
+                assert_close(gradient.map(|gradient| gradient.1), 3.0);
+            }
+            assert_eq!(heightmap.get_elevation(&Site::new(1.1, 0.0)), None);
+            assert_eq!(heightmap.get_elevation(&Site::new(0.0, -1.1)), None);
+        }
+    }
+
+    #[test]
+    fn test_bicubic_is_smoother_than_bilinear() {
+        // a peak at the center of the grid.
+        let heightmap =
+            Heightmap::new(3, 3, vec![0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0]).unwrap();
+        let bilinear = heightmap.get_elevation(&Site::new(0.5, 1.0)).unwrap();
+        let bicubic = heightmap
+            .clone()
+            .interpolation(Interpolation::Bicubic)
+            .get_elevation(&Site::new(0.5, 1.0))
+            .unwrap();
+        assert_eq!(bilinear, 0.5);
+        assert!(bicubic > bilinear);
+    }
+
+    #[test]
+    fn test_no_data_and_sea_level() {
+        let heightmap = Heightmap::new(3, 1, vec![-1.0, 5.0, -9999.0])
+            .unwrap()
+            .no_data(-9999.0)
+            .sea_level(0.0);
+        assert_eq!(heightmap.get_elevation(&Site::new(0.0, 0.0)), None);
+        assert_eq!(heightmap.get_elevation(&Site::new(0.5, 0.0)), Some(2.0));
+        assert_eq!(heightmap.get_elevation(&Site::new(1.0, 0.0)), Some(5.0));
+        assert_eq!(heightmap.get_elevation(&Site::new(1.5, 0.0)), None);
+    }
+
+    #[test]
+    fn test_load_raw_and_pgm() {
+        let bytes = [1.0f32, 2.0, 3.0, 4.0]
+            .iter()
+            .flat_map(|value| value.to_le_bytes())
+            .collect::<Vec<_>>();
+        let heightmap = Heightmap::from_raw_f32_le(2, 2, &bytes).unwrap();
+        assert_eq!(heightmap.get_elevation(&Site::new(1.0, 1.0)), Some(4.0));
+        assert!(Heightmap::from_raw_f32_le(3, 2, &bytes).is_none());
+
+        let mut pgm = b"P5\n# comment\n2 2\n65535\n".to_vec();
+        for value in [0u16, 256, 1000, 65535] {
+            pgm.extend_from_slice(&value.to_be_bytes());
+        }
+        let heightmap = Heightmap::from_pgm(&pgm).unwrap();
+        assert_eq!((heightmap.width(), heightmap.height()), (2, 2));
+        assert_eq!(heightmap.get_elevation(&Site::new(1.0, 0.0)), Some(256.0));
+        assert_eq!(heightmap.get_elevation(&Site::new(1.0, 1.0)), Some(65535.0));
+
+        let pgm = b"P5 3 1 255 \x00\x10\x20";
+        let heightmap = Heightmap::from_pgm(pgm).unwrap();
+        assert_eq!(heightmap.get_elevation(&Site::new(2.0, 0.0)), Some(32.0));
+        assert!(Heightmap::from_pgm(b"P2 1 1 255 0").is_none());
+
+        // trailing bytes are ignored, and missing pixels are rejected.
+        let heightmap = Heightmap::from_pgm(b"P5 2 1 255 \x00\x10\x20").unwrap();
+        assert_eq!(heightmap.get_elevation(&Site::new(1.0, 0.0)), Some(16.0));
+        assert!(Heightmap::from_pgm(b"P5 2 2 255 \x00\x10\x20").is_none());
+    }
+
+    #[test]
+    fn test_size_overflow() {
+        assert!(Heightmap::new(usize::MAX, 2, vec![0.0; 2]).is_none());
+        let pgm = format!("P5 {} 2 65535 \x00\x00", usize::MAX / 2);
+        assert!(Heightmap::from_pgm(pgm.as_bytes()).is_none());
+    }
+}
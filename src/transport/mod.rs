@@ -1,5 +1,18 @@
+pub mod attractor;
 pub mod builder;
+pub mod cache;
+pub mod combinator;
+pub mod config;
+pub mod constraints;
+pub mod context;
+pub mod density;
 mod growth;
+pub mod heightmap;
 pub mod node;
 pub mod params;
+pub mod payload;
+pub mod planner;
+pub mod postprocess;
+pub mod random;
+pub mod tile;
 pub mod traits;
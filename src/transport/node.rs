@@ -18,6 +18,16 @@ impl TransportNode {
         }
     }
 
+    /// Get the stage of the node.
+    pub fn get_stage(&self) -> Stage {
+        self.stage
+    }
+
+    /// Check if the node is placed on a bridge.
+    pub fn is_bridge(&self) -> bool {
+        self.is_bridge
+    }
+
     pub fn path_creates_bridge(&self, other: &Self) -> bool {
         self.is_bridge || other.is_bridge
     }
@@ -1,3 +1,5 @@
+use crate::transport::node::TransportNode;
+
 /// Metrics for a path.
 #[derive(Debug, Clone, PartialEq, Eq, Default)]
 pub struct PathMetrics {
@@ -39,3 +41,96 @@ impl PathMetrics {
         }
     }
 }
+
+/// Path created by the growth, passed to `GrowthMetrics::on_extend`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PathExtension {
+    /// The node which the path starts from.
+    pub start: TransportNode,
+    /// The node which the path ends at.
+    pub end: TransportNode,
+    /// Whether the path is a bridge.
+    pub creates_bridge: bool,
+}
+
+impl PathExtension {
+    /// Get the length of the path.
+    pub fn length(&self) -> f64 {
+        self.start.site.distance(&self.end.site)
+    }
+
+    /// Get the elevation difference from the start to the end of the path.
+    pub fn elevation_gain(&self) -> f64 {
+        self.end.elevation - self.start.elevation
+    }
+}
+
+/// Metrics accumulated along the growth of paths.
+///
+/// The metrics are passed to the providers via `GrowthContext`,
+/// and summarize the paths created before the start node of the path to be extended.
+pub trait GrowthMetrics: Clone + std::fmt::Debug + Default + PartialEq {
+    /// Get the metrics of the paths extended from an origin node.
+    fn initial() -> Self {
+        Self::default()
+    }
+
+    /// Called when the path is extended.
+    fn on_extend(&mut self, extension: &PathExtension);
+
+    /// Called when a branch is created. This is called after `on_extend`.
+    fn on_branch(&mut self);
+
+    /// Called when the stage of the path is incremented. This is called after `on_branch`.
+    fn on_stage(&mut self);
+}
+
+impl GrowthMetrics for PathMetrics {
+    fn initial() -> Self {
+        Self::default().incremented(false, false)
+    }
+
+    fn on_extend(&mut self, _: &PathExtension) {
+        *self = self.incremented(false, false);
+    }
+
+    fn on_branch(&mut self) {
+        self.extend_count_since_last_branched = 0;
+        self.branch_count += 1;
+    }
+
+    fn on_stage(&mut self) {
+        self.extend_count_since_last_staged = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{core::geometry::site::Site, transport::params::numeric::Stage};
+
+    use super::*;
+
+    #[test]
+    fn test_hooks_are_consistent_with_incremented() {
+        let node = TransportNode::new(Site::new(0.0, 0.0), 0.0, Stage::default(), false);
+        let extension = PathExtension {
+            start: node,
+            end: node,
+            creates_bridge: false,
+        };
+        let metrics = PathMetrics::default()
+            .incremented(false, false)
+            .incremented(false, true)
+            .incremented(false, false);
+
+        let mut hooked = metrics.clone();
+        hooked.on_extend(&extension);
+        hooked.on_branch();
+        hooked.on_stage();
+        assert_eq!(hooked, metrics.incremented(true, true));
+
+        let mut hooked = metrics.clone();
+        hooked.on_extend(&extension);
+        assert_eq!(hooked, metrics.incremented(false, false));
+    }
+}
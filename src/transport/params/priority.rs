@@ -1,9 +1,15 @@
-use crate::core::geometry::site::Site;
+use crate::{core::geometry::site::Site, transport::context::GrowthContext};
 
-use super::numeric::Stage;
+use super::{
+    metrics::{GrowthMetrics, PathMetrics},
+    numeric::Stage,
+};
 
 /// Factors for prioritizing the path.
-pub struct PathPrioritizationFactors {
+pub struct PathPrioritizationFactors<'a, M = PathMetrics>
+where
+    M: GrowthMetrics,
+{
     /// The start site of the path.
     pub site_start: Site,
     /// The end site of the path.
@@ -14,4 +20,21 @@ pub struct PathPrioritizationFactors {
     pub stage: Stage,
     /// Whether the path is a bridge.
     pub creates_bridge: bool,
+    /// The density of paths around the end site, if the builder tracks the road density.
+    pub road_density: Option<f64>,
+    /// The context of the path.
+    pub context: &'a GrowthContext<'a, M>,
 }
+
+// Implemented manually so that the factors can be copied into batches for `prioritize_many`
+// without requiring the metrics in the context to be `Copy`.
+impl<M> Clone for PathPrioritizationFactors<'_, M>
+where
+    M: GrowthMetrics,
+{
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<M> Copy for PathPrioritizationFactors<'_, M> where M: GrowthMetrics {}
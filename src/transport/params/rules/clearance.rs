@@ -0,0 +1,71 @@
+use crate::core::geometry::{line_segment::LineSegment, site::Site};
+
+/// Rules to keep paths apart from existing paths running in parallel.
+///
+/// With `Default` values, paths can run close to each other.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClearanceRules {
+    /// Minimum distance from existing paths with similar headings.
+    pub min_parallel_clearance: f64,
+
+    /// Maximum angle (in radian) between two paths to be regarded as parallel.
+    pub parallel_max_radian: f64,
+}
+
+impl Default for ClearanceRules {
+    fn default() -> Self {
+        Self {
+            min_parallel_clearance: 0.0,
+            parallel_max_radian: 0.0,
+        }
+    }
+}
+
+impl ClearanceRules {
+    /// Check if the path from `site_start` to `site_end` keeps the clearance from the parallel paths.
+    ///
+    /// The distance is measured from the end and the midpoint of the path,
+    /// since the start is shared with (or close to) the paths which the path grows from.
+    pub fn keeps_clearance(&self, site_start: Site, site_end: Site, paths: &[LineSegment]) -> bool {
+        if self.min_parallel_clearance <= 0.0 {
+            return true;
+        }
+        let angle = site_start.get_angle(&site_end);
+        let site_middle = site_start.midpoint(&site_end);
+        paths.iter().all(|path| {
+            let diff = angle.diff(&path.0.get_angle(&path.1));
+            let is_parallel = diff.min(std::f64::consts::PI - diff) <= self.parallel_max_radian;
+            !is_parallel
+                || (path.get_distance(&site_end) >= self.min_parallel_clearance
+                    && path.get_distance(&site_middle) >= self.min_parallel_clearance)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keeps_clearance() {
+        let rules = ClearanceRules {
+            min_parallel_clearance: 1.0,
+            parallel_max_radian: std::f64::consts::PI / 6.0,
+        };
+        let paths = [LineSegment::new(Site::new(0.0, 0.0), Site::new(10.0, 0.0))];
+
+        // parallel and close (in both directions)
+        assert!(!rules.keeps_clearance(Site::new(2.0, 0.5), Site::new(4.0, 0.5), &paths));
+        assert!(!rules.keeps_clearance(Site::new(4.0, 0.5), Site::new(2.0, 0.7), &paths));
+        // parallel and far
+        assert!(rules.keeps_clearance(Site::new(2.0, 1.5), Site::new(4.0, 1.5), &paths));
+        // close but not parallel
+        assert!(rules.keeps_clearance(Site::new(2.0, 0.5), Site::new(2.0, 2.5), &paths));
+        // disabled
+        assert!(ClearanceRules::default().keeps_clearance(
+            Site::new(2.0, 0.5),
+            Site::new(4.0, 0.5),
+            &paths
+        ));
+    }
+}
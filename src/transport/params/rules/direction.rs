@@ -8,6 +8,13 @@ pub struct PathDirectionRules {
     /// Number of candidates of the next site to create a path.
     /// This parameter should be an odd number to evaluate the straight path.
     pub comparison_step: usize,
+    /// Affinity to contour lines.
+    ///
+    /// The score of each candidate is added by this value multiplied by the slope avoided by the heading
+    /// (the magnitude of the terrain gradient minus the slope along the heading),
+    /// so the path curves along the contour lines on hills.
+    /// The score should be the same scale as the value from `PathPrioritizator`.
+    pub contour_affinity: f64,
 }
 
 impl Default for PathDirectionRules {
@@ -15,6 +22,7 @@ impl Default for PathDirectionRules {
         Self {
             max_radian: 0.0,
             comparison_step: 1,
+            contour_affinity: 0.0,
         }
     }
 }
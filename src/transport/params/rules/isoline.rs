@@ -0,0 +1,46 @@
+/// Iso-line of the terrain which the path follows.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum IsolineTarget {
+    /// The coastline, where the terrain elevation transitions to `None`.
+    Coastline,
+    /// The contour line of the specified elevation.
+    /// The area where the elevation is `None` is regarded as lower than the elevation.
+    Elevation(f64),
+}
+
+impl IsolineTarget {
+    /// Check if the elevation is on the lower side (e.g. sea) of the iso-line.
+    pub fn is_below(&self, elevation: Option<f64>) -> bool {
+        match (self, elevation) {
+            (_, None) => true,
+            (IsolineTarget::Coastline, Some(_)) => false,
+            (IsolineTarget::Elevation(target), Some(elevation)) => elevation < *target,
+        }
+    }
+}
+
+/// Rules to follow an iso-line of the terrain (e.g. highways along the sea coast).
+///
+/// With `Default` values, the path doesn't follow any iso-line.
+#[derive(Debug, Clone, PartialEq)]
+pub struct IsolineRules {
+    /// The iso-line to follow.
+    pub target: Option<IsolineTarget>,
+
+    /// Distance to keep from the iso-line to the upper side (inland).
+    pub offset: f64,
+
+    /// Number of samples to probe the iso-line on each side of the path.
+    /// The iso-line is probed up to twice the offset.
+    pub check_step: usize,
+}
+
+impl Default for IsolineRules {
+    fn default() -> Self {
+        Self {
+            target: None,
+            offset: 0.0,
+            check_step: 0,
+        }
+    }
+}
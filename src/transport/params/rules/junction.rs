@@ -0,0 +1,22 @@
+/// Rules to keep junctions apart and away from acute angles.
+///
+/// With `Default` values, junctions are created at any angle and spacing.
+#[derive(Debug, Clone, PartialEq)]
+pub struct JunctionRules {
+    /// Minimum angle (in radian) between two paths sharing a node.
+    pub min_angle: f64,
+
+    /// Minimum distance along the paths between a new junction and the other junctions.
+    ///
+    /// This value also prevents branches from being created near other junctions.
+    pub min_intersection_spacing: f64,
+}
+
+impl Default for JunctionRules {
+    fn default() -> Self {
+        Self {
+            min_angle: 0.0,
+            min_intersection_spacing: 0.0,
+        }
+    }
+}
@@ -1,10 +1,21 @@
 use branch::BranchRules;
 use bridge::BridgeRules;
+use clearance::ClearanceRules;
 use direction::PathDirectionRules;
+use isoline::IsolineRules;
+use junction::JunctionRules;
+use switchback::SwitchbackRules;
+
+pub use slope::ElevationDiffLimit;
 
 pub mod branch;
 pub mod bridge;
+pub mod clearance;
 pub mod direction;
+pub mod isoline;
+pub mod junction;
+pub mod slope;
+pub mod switchback;
 
 /// Rules to construct a path.
 #[derive(Debug, Clone, PartialEq)]
@@ -30,6 +41,18 @@ pub struct TransportRules {
 
     /// Rules to create bridges.
     pub bridge_rules: BridgeRules,
+
+    /// Rules to create switchbacks on steep slopes.
+    pub switchback_rules: SwitchbackRules,
+
+    /// Rules to follow an iso-line of the terrain.
+    pub isoline_rules: IsolineRules,
+
+    /// Rules to keep junctions apart and away from acute angles.
+    pub junction_rules: JunctionRules,
+
+    /// Rules to keep paths apart from existing paths running in parallel.
+    pub clearance_rules: ClearanceRules,
 }
 
 impl Default for TransportRules {
@@ -42,6 +65,10 @@ impl Default for TransportRules {
             branch_rules: BranchRules::default(),
             path_direction_rules: PathDirectionRules::default(),
             bridge_rules: BridgeRules::default(),
+            switchback_rules: SwitchbackRules::default(),
+            isoline_rules: IsolineRules::default(),
+            junction_rules: JunctionRules::default(),
+            clearance_rules: ClearanceRules::default(),
         }
     }
 }
@@ -98,35 +125,28 @@ impl TransportRules {
         self.bridge_rules = bridge_rules;
         self
     }
-}
 
-/// The limit of the elevation difference.
-#[derive(Debug, Clone, PartialEq)]
-pub enum ElevationDiffLimit {
-    /// Always allow to construct a path.
-    AlwaysAllow,
-    /// Always deny to construct a path.
-    AlwaysDeny,
-    /// The limit will be proportional to the path length. (specified elevation * path length)
-    Linear(f64),
-    /// The limit will be a non-linear function of the path length.
-    NonLinear(fn(path_length: f64) -> f64),
-}
+    /// Set the rules to create switchbacks on steep slopes.
+    pub fn switchback_rules(mut self, switchback_rules: SwitchbackRules) -> Self {
+        self.switchback_rules = switchback_rules;
+        self
+    }
 
-impl ElevationDiffLimit {
-    /// Get the elevation difference from the path length.
-    fn value(&self, path_length: f64) -> f64 {
-        match self {
-            ElevationDiffLimit::AlwaysAllow => f64::INFINITY,
-            ElevationDiffLimit::AlwaysDeny => f64::NEG_INFINITY,
-            ElevationDiffLimit::Linear(elevation) => elevation * path_length,
-            ElevationDiffLimit::NonLinear(f) => f(path_length),
-        }
+    /// Set the rules to follow an iso-line of the terrain.
+    pub fn isoline_rules(mut self, isoline_rules: IsolineRules) -> Self {
+        self.isoline_rules = isoline_rules;
+        self
     }
 
-    /// Check if the slope is proper to construct a path.
-    pub fn check_slope(&self, elevations: (f64, f64), path_length: f64) -> bool {
-        let elevation_diff = (elevations.1 - elevations.0).abs();
-        elevation_diff <= self.value(path_length)
+    /// Set the rules to keep junctions apart and away from acute angles.
+    pub fn junction_rules(mut self, junction_rules: JunctionRules) -> Self {
+        self.junction_rules = junction_rules;
+        self
+    }
+
+    /// Set the rules to keep paths apart from existing paths running in parallel.
+    pub fn clearance_rules(mut self, clearance_rules: ClearanceRules) -> Self {
+        self.clearance_rules = clearance_rules;
+        self
     }
 }
@@ -0,0 +1,201 @@
+use std::{fmt::Debug, sync::Arc};
+
+use crate::transport::node::TransportNode;
+
+use super::TransportRules;
+
+/// Path whose slope is checked.
+#[derive(Debug, Clone, Copy)]
+pub struct SlopePath<'a> {
+    /// The start node of the path.
+    pub start: TransportNode,
+    /// The end node of the path.
+    pub end: TransportNode,
+    /// The length of the path.
+    pub path_length: f64,
+    /// The rules of the path.
+    ///
+    /// This is `None` for the paths created by post-processes, which have their own rules.
+    pub rules: Option<&'a TransportRules>,
+}
+
+impl<'a> SlopePath<'a> {
+    /// Create a path between two nodes.
+    pub fn new(start: TransportNode, end: TransportNode) -> Self {
+        Self {
+            start,
+            end,
+            path_length: start.site.distance(&end.site),
+            rules: None,
+        }
+    }
+
+    /// Set the rules of the path.
+    pub fn rules(mut self, rules: &'a TransportRules) -> Self {
+        self.rules = Some(rules);
+        self
+    }
+
+    /// Get the absolute elevation difference between the start and the end.
+    pub fn elevation_diff(&self) -> f64 {
+        (self.end.elevation - self.start.elevation).abs()
+    }
+}
+
+/// Limit of the elevation difference of a path.
+pub trait SlopeLimit: Debug + Send + Sync {
+    /// Get the maximum elevation difference allowed for the path.
+    fn max_elevation_diff(&self, path: &SlopePath) -> f64;
+}
+
+/// Slope limit defined by a closure.
+pub struct FnSlopeLimit<F>(pub F)
+where
+    F: Fn(&SlopePath) -> f64 + Send + Sync;
+
+impl<F> Debug for FnSlopeLimit<F>
+where
+    F: Fn(&SlopePath) -> f64 + Send + Sync,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("FnSlopeLimit")
+    }
+}
+
+impl<F> SlopeLimit for FnSlopeLimit<F>
+where
+    F: Fn(&SlopePath) -> f64 + Send + Sync,
+{
+    fn max_elevation_diff(&self, path: &SlopePath) -> f64 {
+        (self.0)(path)
+    }
+}
+
+/// The limit of the elevation difference.
+#[derive(Debug, Clone)]
+pub enum ElevationDiffLimit {
+    /// Always allow to construct a path.
+    AlwaysAllow,
+    /// Always deny to construct a path.
+    AlwaysDeny,
+    /// The limit will be proportional to the path length. (specified elevation * path length)
+    Linear(f64),
+    /// The limit will be a non-linear function of the path length.
+    NonLinear(fn(path_length: f64) -> f64),
+    /// The limit is given by a user-defined implementation.
+    Custom(Arc<dyn SlopeLimit>),
+    /// The strictest of the limits.
+    Min(Vec<ElevationDiffLimit>),
+    /// The loosest of the limits.
+    Max(Vec<ElevationDiffLimit>),
+}
+
+impl PartialEq for ElevationDiffLimit {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::AlwaysAllow, Self::AlwaysAllow) | (Self::AlwaysDeny, Self::AlwaysDeny) => true,
+            (Self::Linear(a), Self::Linear(b)) => a == b,
+            (Self::NonLinear(a), Self::NonLinear(b)) => std::ptr::fn_addr_eq(*a, *b),
+            // custom limits are equal only if they are the same instance.
+            (Self::Custom(a), Self::Custom(b)) => Arc::ptr_eq(a, b),
+            (Self::Min(a), Self::Min(b)) | (Self::Max(a), Self::Max(b)) => a == b,
+            _ => false,
+        }
+    }
+}
+
+impl ElevationDiffLimit {
+    /// Create a limit from a user-defined implementation.
+    pub fn custom<L>(limit: L) -> Self
+    where
+        L: SlopeLimit + 'static,
+    {
+        Self::Custom(Arc::new(limit))
+    }
+
+    /// Create a limit from a closure.
+    pub fn from_fn<F>(f: F) -> Self
+    where
+        F: Fn(&SlopePath) -> f64 + Send + Sync + 'static,
+    {
+        Self::custom(FnSlopeLimit(f))
+    }
+
+    /// Check if the slope is proper to construct a path.
+    pub fn check_slope(&self, path: &SlopePath) -> bool {
+        path.elevation_diff() <= self.max_elevation_diff(path)
+    }
+}
+
+impl From<Box<dyn SlopeLimit>> for ElevationDiffLimit {
+    fn from(limit: Box<dyn SlopeLimit>) -> Self {
+        Self::Custom(Arc::from(limit))
+    }
+}
+
+impl SlopeLimit for ElevationDiffLimit {
+    fn max_elevation_diff(&self, path: &SlopePath) -> f64 {
+        match self {
+            ElevationDiffLimit::AlwaysAllow => f64::INFINITY,
+            ElevationDiffLimit::AlwaysDeny => f64::NEG_INFINITY,
+            ElevationDiffLimit::Linear(elevation) => elevation * path.path_length,
+            ElevationDiffLimit::NonLinear(f) => f(path.path_length),
+            ElevationDiffLimit::Custom(limit) => limit.max_elevation_diff(path),
+            ElevationDiffLimit::Min(limits) => limits
+                .iter()
+                .map(|limit| limit.max_elevation_diff(path))
+                .fold(f64::INFINITY, f64::min),
+            ElevationDiffLimit::Max(limits) => limits
+                .iter()
+                .map(|limit| limit.max_elevation_diff(path))
+                .fold(f64::NEG_INFINITY, f64::max),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{core::geometry::site::Site, transport::params::numeric::Stage};
+
+    use super::*;
+
+    fn create_path(elevation_diff: f64, stage: usize) -> SlopePath<'static> {
+        SlopePath::new(
+            TransportNode::new(Site::new(0.0, 0.0), 0.0, Stage::from_num(stage), false),
+            TransportNode::new(
+                Site::new(3.0, 4.0),
+                elevation_diff,
+                Stage::from_num(stage),
+                false,
+            ),
+        )
+    }
+
+    #[test]
+    fn test_built_in_limits() {
+        assert!(ElevationDiffLimit::AlwaysAllow.check_slope(&create_path(1e9, 0)));
+        assert!(!ElevationDiffLimit::AlwaysDeny.check_slope(&create_path(0.0, 0)));
+        assert!(ElevationDiffLimit::Linear(1.0).check_slope(&create_path(5.0, 0)));
+        assert!(!ElevationDiffLimit::Linear(1.0).check_slope(&create_path(-5.1, 0)));
+    }
+
+    #[test]
+    fn test_custom_and_composed_limits() {
+        // the limit depends on the stage of the path.
+        let by_stage = ElevationDiffLimit::from_fn(|path: &SlopePath| {
+            path.path_length / (1.0 + path.start.get_stage().as_num() as f64)
+        });
+        assert!(by_stage.check_slope(&create_path(5.0, 0)));
+        assert!(!by_stage.check_slope(&create_path(5.0, 1)));
+        assert_eq!(by_stage.clone(), by_stage);
+
+        let min = ElevationDiffLimit::Min(vec![ElevationDiffLimit::Linear(0.5), by_stage.clone()]);
+        assert!(!min.check_slope(&create_path(3.0, 0)));
+        assert!(min.check_slope(&create_path(2.5, 0)));
+
+        let max = ElevationDiffLimit::Max(vec![ElevationDiffLimit::Linear(0.5), by_stage]);
+        assert!(max.check_slope(&create_path(5.0, 0)));
+        assert!(max.check_slope(&create_path(2.5, 1)));
+        assert!(!max.check_slope(&create_path(2.6, 1)));
+    }
+}
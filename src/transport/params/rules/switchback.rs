@@ -0,0 +1,28 @@
+/// Rules to create switchbacks (serpentine paths) on steep slopes.
+///
+/// If all candidates of the path are blocked by the slope limit,
+/// the path zig-zags along the contour with hairpins to gain elevation within the limit.
+///
+/// With `Default` values, the path will never create a switchback.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SwitchbackRules {
+    /// Maximum number of hairpins in a switchback sequence.
+    pub max_hairpins: usize,
+
+    /// Minimum length of a leg between two hairpins.
+    pub min_hairpin_spacing: f64,
+
+    /// Number of candidate angles between the blocked direction and the contour on each side.
+    /// The contour itself is not a candidate.
+    pub check_step: usize,
+}
+
+impl Default for SwitchbackRules {
+    fn default() -> Self {
+        Self {
+            max_hairpins: 0,
+            min_hairpin_spacing: 0.0,
+            check_step: 0,
+        }
+    }
+}
@@ -0,0 +1,97 @@
+use std::collections::BTreeMap;
+
+use crate::core::container::path_network::{NodeId, PathNetwork};
+
+use super::node::TransportNode;
+
+/// Function to create the payload of a node from the node and the payload of its parent node.
+pub type PayloadFn<'a, P> = dyn Fn(&TransportNode, Option<&P>) -> P + 'a;
+
+/// User-defined payloads attached to the nodes of the network.
+pub(crate) struct NodePayloads<'a, P> {
+    create: Box<PayloadFn<'a, P>>,
+    payloads: BTreeMap<NodeId, P>,
+}
+
+impl<'a, P> NodePayloads<'a, P> {
+    /// Create payloads for all nodes in the network.
+    pub(crate) fn from_network(
+        network: &PathNetwork<TransportNode>,
+        create: Box<PayloadFn<'a, P>>,
+    ) -> Self {
+        let payloads = network
+            .nodes_iter()
+            .map(|(node_id, node)| (node_id, create(node, None)))
+            .collect();
+        Self { create, payloads }
+    }
+
+    /// Create the payload of the added node.
+    pub(crate) fn on_add(&mut self, node_id: NodeId, node: &TransportNode, parent: Option<NodeId>) {
+        let payload = (self.create)(node, parent.and_then(|parent| self.payloads.get(&parent)));
+        self.payloads.insert(node_id, payload);
+    }
+
+    /// Remove the payload of the removed node.
+    pub(crate) fn on_remove(&mut self, node_id: NodeId) {
+        self.payloads.remove(&node_id);
+    }
+
+    /// Synchronize the payloads with the network modified directly.
+    ///
+    /// Payloads of removed nodes are dropped, and added nodes get payloads without parents.
+    pub(crate) fn sync(&mut self, network: &PathNetwork<TransportNode>) {
+        self.payloads
+            .retain(|node_id, _| network.get_node(*node_id).is_some());
+        for (node_id, node) in network.nodes_iter() {
+            if !self.payloads.contains_key(&node_id) {
+                let payload = (self.create)(node, None);
+                self.payloads.insert(node_id, payload);
+            }
+        }
+    }
+
+    pub(crate) fn get(&self, node_id: NodeId) -> Option<&P> {
+        self.payloads.get(&node_id)
+    }
+
+    pub(crate) fn payloads(&self) -> &BTreeMap<NodeId, P> {
+        &self.payloads
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{core::geometry::site::Site, transport::params::numeric::Stage};
+
+    use super::*;
+
+    #[test]
+    fn test_node_payloads() {
+        let create_node =
+            |x: f64| TransportNode::new(Site::new(x, 0.0), 0.0, Stage::from_num(0), false);
+        let mut network = PathNetwork::new();
+        let node0 = network.add_node(create_node(0.0));
+
+        // the payload is the depth from the node without parent.
+        let mut payloads = NodePayloads::from_network(
+            &network,
+            Box::new(|_: &TransportNode, parent: Option<&usize>| {
+                parent.map_or(0, |depth| depth + 1)
+            }),
+        );
+        let node1 = network.add_node(create_node(1.0));
+        payloads.on_add(node1, &create_node(1.0), Some(node0));
+        let node2 = network.add_node(create_node(2.0));
+        payloads.on_add(node2, &create_node(2.0), Some(node1));
+        assert_eq!(payloads.get(node0), Some(&0));
+        assert_eq!(payloads.get(node2), Some(&2));
+
+        network.remove_node(node1);
+        let node3 = network.add_node(create_node(3.0));
+        payloads.sync(&network);
+        assert_eq!(payloads.get(node1), None);
+        assert_eq!(payloads.get(node3), Some(&0));
+        assert_eq!(payloads.payloads().len(), 3);
+    }
+}
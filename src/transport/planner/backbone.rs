@@ -0,0 +1,125 @@
+use crate::core::geometry::site::Site;
+
+/// Type of the backbone graph connecting towns.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BackboneType {
+    /// Minimum spanning tree over the towns.
+    ///
+    /// All towns are connected with the minimum total length of links.
+    #[default]
+    MinimumSpanningTree,
+    /// Relative neighborhood graph over the towns.
+    ///
+    /// Two towns are linked if there is no other town closer to both of them.
+    /// This graph contains the minimum spanning tree and creates some loops.
+    RelativeNeighborhoodGraph,
+}
+
+impl BackboneType {
+    /// Calculate the links between towns as pairs of indices.
+    pub fn links(&self, towns: &[Site]) -> Vec<(usize, usize)> {
+        match self {
+            BackboneType::MinimumSpanningTree => minimum_spanning_tree(towns),
+            BackboneType::RelativeNeighborhoodGraph => relative_neighborhood_graph(towns),
+        }
+    }
+}
+
+/// Calculate the minimum spanning tree by Prim's algorithm.
+fn minimum_spanning_tree(towns: &[Site]) -> Vec<(usize, usize)> {
+    if towns.is_empty() {
+        return Vec::new();
+    }
+
+    let mut in_tree = vec![false; towns.len()];
+    // (distance, index of the closest town in the tree)
+    let mut closest = vec![(f64::INFINITY, 0); towns.len()];
+    let mut links = Vec::with_capacity(towns.len() - 1);
+
+    in_tree[0] = true;
+    (1..towns.len()).for_each(|i| closest[i] = (towns[0].distance_2(&towns[i]), 0));
+
+    for _ in 1..towns.len() {
+        let next = (0..towns.len())
+            .filter(|i| !in_tree[*i])
+            .min_by(|a, b| closest[*a].0.total_cmp(&closest[*b].0));
+        let next = if let Some(next) = next {
+            next
+        } else {
+            break;
+        };
+
+        in_tree[next] = true;
+        links.push((closest[next].1, next));
+
+        (0..towns.len()).filter(|i| !in_tree[*i]).for_each(|i| {
+            let distance = towns[next].distance_2(&towns[i]);
+            if distance < closest[i].0 {
+                closest[i] = (distance, next);
+            }
+        });
+    }
+
+    links
+}
+
+/// Calculate the relative neighborhood graph.
+fn relative_neighborhood_graph(towns: &[Site]) -> Vec<(usize, usize)> {
+    let mut links = Vec::new();
+    for i in 0..towns.len() {
+        for j in i + 1..towns.len() {
+            let distance = towns[i].distance_2(&towns[j]);
+            let blocked = (0..towns.len()).filter(|k| *k != i && *k != j).any(|k| {
+                towns[i]
+                    .distance_2(&towns[k])
+                    .max(towns[j].distance_2(&towns[k]))
+                    < distance
+            });
+            if !blocked {
+                links.push((i, j));
+            }
+        }
+    }
+    links
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn normalized(mut links: Vec<(usize, usize)>) -> Vec<(usize, usize)> {
+        links = links
+            .into_iter()
+            .map(|(a, b)| (a.min(b), a.max(b)))
+            .collect();
+        links.sort();
+        links
+    }
+
+    #[test]
+    fn test_minimum_spanning_tree() {
+        let towns = vec![
+            Site::new(0.0, 0.0),
+            Site::new(10.0, 0.0),
+            Site::new(10.0, 1.0),
+            Site::new(0.0, 3.0),
+        ];
+        let links = normalized(BackboneType::MinimumSpanningTree.links(&towns));
+        assert_eq!(links, vec![(0, 1), (0, 3), (1, 2)]);
+
+        assert!(BackboneType::MinimumSpanningTree.links(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_relative_neighborhood_graph() {
+        // square: all sides are linked, but diagonals are not.
+        let towns = vec![
+            Site::new(0.0, 0.0),
+            Site::new(1.0, 0.0),
+            Site::new(1.0, 1.0),
+            Site::new(0.0, 1.0),
+        ];
+        let links = normalized(BackboneType::RelativeNeighborhoodGraph.links(&towns));
+        assert_eq!(links, vec![(0, 1), (0, 3), (1, 2), (2, 3)]);
+    }
+}
@@ -0,0 +1,200 @@
+use backbone::BackboneType;
+use route::{RouteCosts, RouteSearch, RouteSite};
+
+use crate::core::geometry::site::Site;
+
+use super::{
+    constraints::GrowthConstraints, params::rules::TransportRules, traits::TerrainProvider,
+};
+
+pub mod backbone;
+pub mod route;
+
+/// Planner of highways between towns.
+///
+/// The planner links towns by a backbone graph and routes each link by least-cost search over the terrain.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HighwayPlanner {
+    /// Type of the backbone graph connecting towns.
+    pub backbone_type: BackboneType,
+
+    /// Rules of highways.
+    ///
+    /// `path_normal_length` is used as the spacing of the search lattice,
+    /// and `path_slope_elevation_diff_limit` and `bridge_rules` limit the moves on the lattice.
+    pub rules: TransportRules,
+
+    /// Cost per unit of elevation difference, added to the length of the path.
+    pub elevation_cost: f64,
+
+    /// Multiplier of the length of bridges.
+    pub bridge_cost: f64,
+
+    /// Maximum number of lattice points to be expanded for each link.
+    pub max_search_count: usize,
+}
+
+impl Default for HighwayPlanner {
+    fn default() -> Self {
+        Self {
+            backbone_type: BackboneType::default(),
+            rules: TransportRules::default(),
+            elevation_cost: 0.0,
+            bridge_cost: 1.0,
+            max_search_count: 100000,
+        }
+    }
+}
+
+impl HighwayPlanner {
+    /// Set the type of the backbone graph connecting towns.
+    pub fn backbone_type(mut self, backbone_type: BackboneType) -> Self {
+        self.backbone_type = backbone_type;
+        self
+    }
+
+    /// Set the rules of highways.
+    pub fn rules(mut self, rules: TransportRules) -> Self {
+        self.rules = rules;
+        self
+    }
+
+    /// Set the cost per unit of elevation difference.
+    pub fn elevation_cost(mut self, elevation_cost: f64) -> Self {
+        self.elevation_cost = elevation_cost;
+        self
+    }
+
+    /// Set the multiplier of the length of bridges.
+    pub fn bridge_cost(mut self, bridge_cost: f64) -> Self {
+        self.bridge_cost = bridge_cost;
+        self
+    }
+
+    /// Set the maximum number of lattice points to be expanded for each link.
+    pub fn max_search_count(mut self, max_search_count: usize) -> Self {
+        self.max_search_count = max_search_count;
+        self
+    }
+
+    /// Plan routes of highways between towns.
+    ///
+    /// Links which cannot be routed are skipped.
+    pub fn plan<TP>(
+        &self,
+        terrain_provider: &TP,
+        constraints: &GrowthConstraints,
+        towns: &[Site],
+    ) -> Vec<Vec<RouteSite>>
+    where
+        TP: TerrainProvider,
+    {
+        let search = RouteSearch::new(
+            terrain_provider,
+            constraints,
+            &self.rules,
+            RouteCosts {
+                elevation_cost: self.elevation_cost,
+                bridge_cost: self.bridge_cost,
+                max_search_count: self.max_search_count,
+            },
+        );
+
+        self.backbone_type
+            .links(towns)
+            .into_iter()
+            .filter_map(|(start, end)| search.search(towns[start], towns[end]))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::transport::params::rules::{bridge::BridgeRules, ElevationDiffLimit};
+
+    use super::*;
+
+    /// Flat terrain with a river at 2.0 < x < 4.0.
+    struct RiverTerrain;
+
+    impl TerrainProvider for RiverTerrain {
+        fn get_elevation(&self, site: &Site) -> Option<f64> {
+            if site.x > 2.0 && site.x < 4.0 {
+                None
+            } else {
+                Some(0.0)
+            }
+        }
+    }
+
+    /// Terrain with a ridge at x = 5.0.
+    struct RidgeTerrain;
+
+    impl TerrainProvider for RidgeTerrain {
+        fn get_elevation(&self, site: &Site) -> Option<f64> {
+            Some((10.0 - (site.x - 5.0).abs() * 10.0).max(0.0) * (1.0 - site.y.abs() / 10.0))
+        }
+    }
+
+    #[test]
+    fn test_route_over_flat_terrain() {
+        let planner =
+            HighwayPlanner::default().rules(TransportRules::default().path_normal_length(1.0));
+        let routes = planner.plan(
+            &RiverTerrain,
+            &GrowthConstraints::default(),
+            &[Site::new(-5.0, 0.3), Site::new(0.0, 5.0)],
+        );
+        assert_eq!(routes.len(), 1);
+
+        let route = &routes[0];
+        assert_eq!(route.first().unwrap().site, Site::new(-5.0, 0.3));
+        assert_eq!(route.last().unwrap().site, Site::new(0.0, 5.0));
+        assert!(route.iter().all(|site| !site.is_bridge));
+        // diagonal moves make the route shorter than manhattan distance.
+        let length = route
+            .windows(2)
+            .map(|w| w[0].site.distance(&w[1].site))
+            .sum::<f64>();
+        assert!(length < 8.0);
+    }
+
+    #[test]
+    fn test_route_with_bridge() {
+        let towns = [Site::new(0.0, 0.0), Site::new(6.0, 0.0)];
+        let rules = TransportRules::default().path_normal_length(1.0);
+
+        // no bridge is allowed
+        let planner = HighwayPlanner::default().rules(rules.clone());
+        let routes = planner.plan(&RiverTerrain, &GrowthConstraints::default(), &towns);
+        assert!(routes.is_empty());
+
+        // bridge is allowed
+        let planner = HighwayPlanner::default().rules(rules.bridge_rules(BridgeRules {
+            max_bridge_length: 3.0,
+            check_step: 3,
+        }));
+        let routes = planner.plan(&RiverTerrain, &GrowthConstraints::default(), &towns);
+        assert_eq!(routes.len(), 1);
+        assert_eq!(routes[0].iter().filter(|site| site.is_bridge).count(), 1);
+    }
+
+    #[test]
+    fn test_route_avoids_steep_slope() {
+        let towns = [Site::new(0.0, 0.0), Site::new(10.0, 0.0)];
+        let planner = HighwayPlanner::default().rules(
+            TransportRules::default()
+                .path_normal_length(1.0)
+                .path_slope_elevation_diff_limit(ElevationDiffLimit::Linear(5.0)),
+        );
+        let routes = planner.plan(&RidgeTerrain, &GrowthConstraints::default(), &towns);
+        assert_eq!(routes.len(), 1);
+
+        // the route detours to the lower part of the ridge.
+        let max_elevation = routes[0]
+            .iter()
+            .map(|site| site.elevation)
+            .fold(0.0, f64::max);
+        assert!(max_elevation <= 5.0 * 2.0_f64.sqrt());
+    }
+}
@@ -0,0 +1,278 @@
+use std::collections::{BTreeMap, BinaryHeap};
+
+use crate::{
+    core::geometry::site::Site,
+    transport::{
+        constraints::GrowthConstraints,
+        node::TransportNode,
+        params::{
+            numeric::Stage,
+            rules::{slope::SlopePath, TransportRules},
+        },
+        traits::TerrainProvider,
+    },
+};
+
+/// Directions of the moves on the lattice.
+const DIRECTIONS: [(i64, i64); 8] = [
+    (1, 0),
+    (1, 1),
+    (0, 1),
+    (-1, 1),
+    (-1, 0),
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+];
+
+/// Point on the lattice.
+type LatticePoint = (i64, i64);
+
+/// A site of the route.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RouteSite {
+    pub site: Site,
+    pub elevation: f64,
+    /// Whether the path from the previous site is a bridge.
+    pub is_bridge: bool,
+}
+
+/// Costs of the search.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(super) struct RouteCosts {
+    pub elevation_cost: f64,
+    pub bridge_cost: f64,
+    pub max_search_count: usize,
+}
+
+/// State in the priority queue of the search.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct SearchState {
+    point: LatticePoint,
+    cost: f64,
+    score: f64,
+}
+
+impl Eq for SearchState {}
+
+impl PartialOrd for SearchState {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for SearchState {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        // reversed to pop the state with the lowest score first.
+        other.score.total_cmp(&self.score)
+    }
+}
+
+/// Least-cost search on a square lattice over the terrain.
+pub(super) struct RouteSearch<'a, TP>
+where
+    TP: TerrainProvider,
+{
+    terrain_provider: &'a TP,
+    constraints: &'a GrowthConstraints,
+    rules: &'a TransportRules,
+    costs: RouteCosts,
+}
+
+impl<'a, TP> RouteSearch<'a, TP>
+where
+    TP: TerrainProvider,
+{
+    pub fn new(
+        terrain_provider: &'a TP,
+        constraints: &'a GrowthConstraints,
+        rules: &'a TransportRules,
+        costs: RouteCosts,
+    ) -> Self {
+        Self {
+            terrain_provider,
+            constraints,
+            rules,
+            costs,
+        }
+    }
+
+    fn step(&self) -> f64 {
+        self.rules.path_normal_length
+    }
+
+    fn site_of(&self, point: LatticePoint) -> Site {
+        Site::new(point.0 as f64 * self.step(), point.1 as f64 * self.step())
+    }
+
+    fn point_of(&self, site: Site) -> LatticePoint {
+        (
+            (site.x / self.step()).round() as i64,
+            (site.y / self.step()).round() as i64,
+        )
+    }
+
+    /// Lengths of moves in lattice units. The first one is the normal move and the rest are bridges.
+    fn move_lengths(&self) -> Vec<i64> {
+        let bridge_rules = &self.rules.bridge_rules;
+        let mut lengths = (1..=bridge_rules.check_step)
+            .map(|i| {
+                let bridge_length =
+                    bridge_rules.max_bridge_length * (i as f64) / (bridge_rules.check_step as f64);
+                1 + (bridge_length / self.step()).round() as i64
+            })
+            .filter(|length| *length > 1)
+            .collect::<Vec<_>>();
+        lengths.dedup();
+        lengths.insert(0, 1);
+        lengths
+    }
+
+    /// Calculate the cost of the move, or None if the move is not allowed.
+    fn move_cost(&self, from: (Site, f64), to: Site, is_bridge: bool) -> Option<(f64, f64)> {
+        let elevation = self.terrain_provider.get_elevation(&to)?;
+        let length = from.0.distance(&to);
+        let stage = Stage::from_num(0);
+        if !self.rules.path_slope_elevation_diff_limit.check_slope(
+            &SlopePath::new(
+                TransportNode::new(from.0, from.1, stage, false),
+                TransportNode::new(to, elevation, stage, is_bridge),
+            )
+            .rules(self.rules),
+        ) {
+            return None;
+        }
+        if !self.constraints.permits_path(from.0, to, is_bridge) {
+            return None;
+        }
+        let length_cost = if is_bridge {
+            length * self.costs.bridge_cost
+        } else {
+            length
+        };
+        let cost = length_cost + (elevation - from.1).abs() * self.costs.elevation_cost;
+        Some((cost, elevation))
+    }
+
+    /// Search the least-cost route from `start` to `goal`.
+    ///
+    /// The route starts and ends at the given sites, and the other sites are placed on the lattice.
+    pub fn search(&self, start: Site, goal: Site) -> Option<Vec<RouteSite>> {
+        if self.step() <= 0.0 {
+            return None;
+        }
+        let start_elevation = self.terrain_provider.get_elevation(&start)?;
+        let goal_elevation = self.terrain_provider.get_elevation(&goal)?;
+
+        let point_start = self.point_of(start);
+        let point_goal = self.point_of(goal);
+        let move_lengths = self.move_lengths();
+
+        let mut heap = BinaryHeap::new();
+        let mut scores: BTreeMap<LatticePoint, (f64, f64)> = BTreeMap::new();
+        let mut came_from: BTreeMap<LatticePoint, (LatticePoint, bool)> = BTreeMap::new();
+
+        let (cost_first, elevation_first) =
+            self.move_cost((start, start_elevation), self.site_of(point_start), false)?;
+        scores.insert(point_start, (cost_first, elevation_first));
+        heap.push(SearchState {
+            point: point_start,
+            cost: cost_first,
+            score: cost_first + self.site_of(point_start).distance(&goal),
+        });
+
+        let mut search_count = 0;
+        while let Some(SearchState { point, cost, .. }) = heap.pop() {
+            let (cost_best, elevation) = scores[&point];
+            if cost > cost_best {
+                // outdated state
+                continue;
+            }
+            if point == point_goal {
+                break;
+            }
+            search_count += 1;
+            if search_count > self.costs.max_search_count {
+                return None;
+            }
+
+            let site = self.site_of(point);
+            for direction in DIRECTIONS {
+                for (i, length) in move_lengths.iter().enumerate() {
+                    let next = (
+                        point.0 + direction.0 * length,
+                        point.1 + direction.1 * length,
+                    );
+                    let site_next = self.site_of(next);
+                    let is_bridge = i > 0;
+                    let (move_cost, elevation_next) = if let Some(cost) =
+                        self.move_cost((site, elevation), site_next, is_bridge)
+                    {
+                        cost
+                    } else {
+                        continue;
+                    };
+                    let cost_next = cost + move_cost;
+                    if scores
+                        .get(&next)
+                        .is_none_or(|(cost_prev, _)| cost_next < *cost_prev)
+                    {
+                        scores.insert(next, (cost_next, elevation_next));
+                        came_from.insert(next, (point, is_bridge));
+                        heap.push(SearchState {
+                            point: next,
+                            cost: cost_next,
+                            score: cost_next + site_next.distance(&goal),
+                        });
+                    }
+                    // bridges are checked only if the shorter moves are not allowed.
+                    break;
+                }
+            }
+        }
+
+        let (_, elevation_goal_point) = *scores.get(&point_goal)?;
+        self.move_cost(
+            (self.site_of(point_goal), elevation_goal_point),
+            goal,
+            false,
+        )?;
+
+        // reconstruct the route from the goal.
+        let mut route = vec![RouteSite {
+            site: goal,
+            elevation: goal_elevation,
+            is_bridge: false,
+        }];
+        let mut point = point_goal;
+        loop {
+            let (_, elevation) = scores[&point];
+            let (is_bridge, prev) = if let Some((prev, is_bridge)) = came_from.get(&point) {
+                (*is_bridge, Some(*prev))
+            } else {
+                (false, None)
+            };
+            route.push(RouteSite {
+                site: self.site_of(point),
+                elevation,
+                is_bridge,
+            });
+            if let Some(prev) = prev {
+                point = prev;
+            } else {
+                break;
+            }
+        }
+        route.push(RouteSite {
+            site: start,
+            elevation: start_elevation,
+            is_bridge: false,
+        });
+        route.reverse();
+
+        // remove duplicated sites (when the town is exactly on the lattice).
+        route.dedup_by(|a, b| a.site == b.site);
+
+        Some(route)
+    }
+}
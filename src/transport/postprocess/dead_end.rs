@@ -0,0 +1,510 @@
+use crate::{
+    core::{
+        container::path_network::{NodeId, PathNetwork},
+        geometry::{angle::Angle, line_segment::LineSegment, site::Site},
+    },
+    transport::{
+        node::TransportNode,
+        params::rules::{slope::SlopePath, ElevationDiffLimit},
+        traits::TerrainProvider,
+    },
+};
+
+use super::ring_collides;
+
+/// Number of sites to check the terrain along the connecting path.
+const BRIDGE_CHECK_STEP: usize = 8;
+
+/// Treatment of dead ends (nodes with only one path).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DeadEndTreatment {
+    /// Remove dead ends which reach a junction within the number of paths.
+    Prune { max_segments: usize },
+    /// Connect dead ends to the nearest reachable node within the distance.
+    Connect { max_length: f64 },
+    /// Convert dead ends into cul-de-sac bulbs with the radius and the number of nodes of the bulb.
+    CulDeSac { radius: f64, node_count: usize },
+}
+
+/// Rules to treat dead ends.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeadEndRules {
+    /// The treatment of dead ends.
+    pub treatment: DeadEndTreatment,
+
+    /// Target ratio of dead ends to all nodes.
+    ///
+    /// Dead ends are treated until the ratio becomes less than or equal to this value.
+    pub target_ratio: f64,
+
+    /// The limit of the elevation difference of the paths to be created.
+    pub slope_limit: ElevationDiffLimit,
+
+    /// Maximum length of bridges to connect dead ends.
+    pub max_bridge_length: f64,
+}
+
+impl DeadEndRules {
+    /// Create new rules with the treatment.
+    pub fn new(treatment: DeadEndTreatment) -> Self {
+        Self {
+            treatment,
+            target_ratio: 0.0,
+            slope_limit: ElevationDiffLimit::AlwaysAllow,
+            max_bridge_length: 0.0,
+        }
+    }
+
+    /// Set the target ratio of dead ends to all nodes.
+    pub fn target_ratio(mut self, target_ratio: f64) -> Self {
+        self.target_ratio = target_ratio;
+        self
+    }
+
+    /// Set the limit of the elevation difference of the paths to be created.
+    pub fn slope_limit(mut self, slope_limit: ElevationDiffLimit) -> Self {
+        self.slope_limit = slope_limit;
+        self
+    }
+
+    /// Set the maximum length of bridges to connect dead ends.
+    pub fn max_bridge_length(mut self, max_bridge_length: f64) -> Self {
+        self.max_bridge_length = max_bridge_length;
+        self
+    }
+}
+
+/// Counter of dead ends to check the target ratio.
+struct DeadEndCounter {
+    dead_ends: usize,
+    nodes: usize,
+}
+
+impl DeadEndCounter {
+    fn reaches(&self, target_ratio: f64) -> bool {
+        self.dead_ends as f64 <= target_ratio * self.nodes as f64
+    }
+}
+
+fn is_dead_end(network: &PathNetwork<TransportNode>, node_id: NodeId) -> bool {
+    network
+        .neighbors_iter(node_id)
+        .is_some_and(|neighbors| neighbors.count() == 1)
+}
+
+/// Treat dead ends in the network.
+pub(crate) fn treat_dead_ends<TP>(
+    network: &mut PathNetwork<TransportNode>,
+    terrain_provider: &TP,
+    rules: &DeadEndRules,
+) where
+    TP: TerrainProvider,
+{
+    let mut dead_ends = network
+        .nodes_iter()
+        .map(|(node_id, _)| node_id)
+        .filter(|node_id| is_dead_end(network, *node_id))
+        .collect::<Vec<_>>();
+
+    let mut counter = DeadEndCounter {
+        dead_ends: dead_ends.len(),
+        nodes: network.nodes_iter().count(),
+    };
+
+    if let DeadEndTreatment::Prune { .. } = rules.treatment {
+        // shorter dead ends are pruned first.
+        dead_ends.sort_by_cached_key(|node_id| {
+            dead_end_chain(network, *node_id).map_or(usize::MAX, |chain| chain.len())
+        });
+    }
+
+    for dead_end_id in dead_ends {
+        if counter.reaches(rules.target_ratio) {
+            break;
+        }
+        // the dead end may be already treated.
+        if !is_dead_end(network, dead_end_id) {
+            continue;
+        }
+        match rules.treatment {
+            DeadEndTreatment::Prune { max_segments } => {
+                prune(network, &mut counter, dead_end_id, max_segments);
+            }
+            DeadEndTreatment::Connect { max_length } => {
+                connect(
+                    network,
+                    terrain_provider,
+                    rules,
+                    &mut counter,
+                    dead_end_id,
+                    max_length,
+                );
+            }
+            DeadEndTreatment::CulDeSac { radius, node_count } => {
+                add_cul_de_sac(
+                    network,
+                    terrain_provider,
+                    rules,
+                    &mut counter,
+                    dead_end_id,
+                    (radius, node_count),
+                );
+            }
+        }
+    }
+}
+
+/// Get nodes from the dead end to the node before the nearest junction.
+///
+/// Returns `None` if the chain doesn't reach any junction.
+fn dead_end_chain(
+    network: &PathNetwork<TransportNode>,
+    dead_end_id: NodeId,
+) -> Option<Vec<NodeId>> {
+    let mut chain = vec![dead_end_id];
+    let mut prev = None;
+    let mut current = dead_end_id;
+    loop {
+        let neighbors = network
+            .neighbors_iter(current)?
+            .map(|(node_id, _)| node_id)
+            .collect::<Vec<_>>();
+        if neighbors.len() > 2 {
+            chain.pop();
+            return Some(chain);
+        }
+        let next = neighbors
+            .into_iter()
+            .find(|node_id| Some(*node_id) != prev)?;
+        if next == dead_end_id {
+            return None;
+        }
+        prev = Some(current);
+        current = next;
+        chain.push(current);
+    }
+}
+
+/// Remove the dead end and the nodes up to the nearest junction.
+fn prune(
+    network: &mut PathNetwork<TransportNode>,
+    counter: &mut DeadEndCounter,
+    dead_end_id: NodeId,
+    max_segments: usize,
+) -> Option<()> {
+    let chain = dead_end_chain(network, dead_end_id)?;
+    if chain.len() > max_segments {
+        return None;
+    }
+    chain.iter().for_each(|node_id| {
+        network.remove_node(*node_id);
+    });
+    counter.dead_ends -= 1;
+    counter.nodes -= chain.len();
+    Some(())
+}
+
+/// Connect the dead end to the nearest reachable node.
+fn connect<TP>(
+    network: &mut PathNetwork<TransportNode>,
+    terrain_provider: &TP,
+    rules: &DeadEndRules,
+    counter: &mut DeadEndCounter,
+    dead_end_id: NodeId,
+    max_length: f64,
+) -> Option<()>
+where
+    TP: TerrainProvider,
+{
+    let dead_end = *network.get_node(dead_end_id)?;
+    let (prev_id, prev) = network
+        .neighbors_iter(dead_end_id)?
+        .map(|(node_id, node)| (node_id, *node))
+        .next()?;
+    if dead_end.is_bridge {
+        return None;
+    }
+    let angle_back = dead_end.site.get_angle(&prev.site);
+
+    let (target_id, target, creates_bridge) = network
+        .nodes_around_site_iter(dead_end.site, max_length)
+        .filter(|node_id| **node_id != dead_end_id && **node_id != prev_id)
+        .filter_map(|node_id| Some((*node_id, *network.get_node(*node_id)?)))
+        .filter(|(_, node)| !node.is_bridge)
+        .filter(|(_, node)| {
+            // the path should not turn back.
+            dead_end.site.get_angle(&node.site).diff(&angle_back) >= std::f64::consts::FRAC_PI_2
+        })
+        .filter(|(_, node)| {
+            rules
+                .slope_limit
+                .check_slope(&SlopePath::new(dead_end, *node))
+        })
+        .filter(|(node_id, node)| {
+            // the path should not cross other paths.
+            let line = LineSegment::new(dead_end.site, node.site);
+            !network
+                .paths_touching_rect_iter(dead_end.site, node.site)
+                .filter(|(start, end)| {
+                    ![dead_end_id, *node_id].contains(start)
+                        && ![dead_end_id, *node_id].contains(end)
+                })
+                .filter_map(|(start, end)| {
+                    Some(LineSegment::new(
+                        network.get_node(*start)?.site,
+                        network.get_node(*end)?.site,
+                    ))
+                })
+                .any(|path| path.get_intersection(&line).is_some())
+        })
+        .filter_map(|(node_id, node)| {
+            // the path over the area without elevation is a bridge.
+            let creates_bridge = (1..BRIDGE_CHECK_STEP).any(|i| {
+                let t = i as f64 / BRIDGE_CHECK_STEP as f64;
+                let site = Site::new(
+                    dead_end.site.x + (node.site.x - dead_end.site.x) * t,
+                    dead_end.site.y + (node.site.y - dead_end.site.y) * t,
+                );
+                terrain_provider.get_elevation(&site).is_none()
+            });
+            if creates_bridge && dead_end.site.distance(&node.site) > rules.max_bridge_length {
+                return None;
+            }
+            Some((node_id, node, creates_bridge))
+        })
+        .min_by(|a, b| {
+            a.1.site
+                .distance_2(&dead_end.site)
+                .total_cmp(&b.1.site.distance_2(&dead_end.site))
+        })?;
+
+    if is_dead_end(network, target_id) {
+        counter.dead_ends -= 1;
+    }
+    counter.dead_ends -= 1;
+
+    if creates_bridge {
+        let bridge_node_id = network.add_node(TransportNode::new(
+            dead_end.site.midpoint(&target.site),
+            (dead_end.elevation + target.elevation) / 2.0,
+            dead_end.path_stage(&target),
+            true,
+        ));
+        network.add_path(dead_end_id, bridge_node_id);
+        network.add_path(bridge_node_id, target_id);
+        counter.nodes += 1;
+    } else {
+        network.add_path(dead_end_id, target_id);
+    }
+    Some(())
+}
+
+/// Convert the dead end into a cul-de-sac bulb.
+///
+/// The bulb is a ring of nodes passing through the dead end.
+fn add_cul_de_sac<TP>(
+    network: &mut PathNetwork<TransportNode>,
+    terrain_provider: &TP,
+    rules: &DeadEndRules,
+    counter: &mut DeadEndCounter,
+    dead_end_id: NodeId,
+    (radius, node_count): (f64, usize),
+) -> Option<()>
+where
+    TP: TerrainProvider,
+{
+    if radius <= 0.0 || node_count < 3 {
+        return None;
+    }
+    let dead_end = *network.get_node(dead_end_id)?;
+    let (_, prev) = network.neighbors_iter(dead_end_id)?.next()?;
+    if dead_end.is_bridge {
+        return None;
+    }
+
+    let center = dead_end
+        .site
+        .extend(prev.site.get_angle(&dead_end.site), radius);
+
+    // the bulb must not contain other nodes.
+    if network
+        .nodes_around_site_iter(center, radius)
+        .any(|node_id| *node_id != dead_end_id)
+    {
+        return None;
+    }
+
+    let angle_start = center.get_angle(&dead_end.site);
+    let ring = std::iter::once(Some(dead_end))
+        .chain((1..node_count).map(|i| {
+            let angle = Angle::new(
+                angle_start.radian()
+                    + 2.0 * std::f64::consts::PI * (i as f64) / (node_count as f64),
+            );
+            let site = center.extend(angle, radius);
+            let elevation = terrain_provider.get_elevation(&site)?;
+            Some(TransportNode::new(site, elevation, dead_end.stage, false))
+        }))
+        .collect::<Option<Vec<_>>>()?;
+
+    // check slopes and collisions of the bulb.
+    let slope_ok = (0..ring.len()).all(|i| {
+        let (node, node_next) = (&ring[i], &ring[(i + 1) % ring.len()]);
+        rules
+            .slope_limit
+            .check_slope(&SlopePath::new(*node, *node_next))
+    });
+    let ring_sites = ring.iter().map(|node| node.site).collect::<Vec<_>>();
+    if !slope_ok || ring_collides(network, &ring_sites, center, radius, dead_end_id) {
+        return None;
+    }
+
+    let ring_ids = std::iter::once(dead_end_id)
+        .chain(ring.iter().skip(1).map(|node| network.add_node(*node)))
+        .collect::<Vec<_>>();
+    (0..ring_ids.len()).for_each(|i| {
+        network.add_path(ring_ids[i], ring_ids[(i + 1) % ring_ids.len()]);
+    });
+
+    counter.dead_ends -= 1;
+    counter.nodes += node_count - 1;
+    Some(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::transport::params::numeric::Stage;
+
+    use super::*;
+
+    /// Flat terrain with a river at 1.4 < x < 1.6.
+    struct RiverTerrain;
+
+    impl TerrainProvider for RiverTerrain {
+        fn get_elevation(&self, site: &Site) -> Option<f64> {
+            if site.x > 1.4 && site.x < 1.6 {
+                None
+            } else {
+                Some(0.0)
+            }
+        }
+    }
+
+    fn create_network(
+        sites: &[(f64, f64)],
+        paths: &[(usize, usize)],
+    ) -> (PathNetwork<TransportNode>, Vec<NodeId>) {
+        let mut network = PathNetwork::new();
+        let node_ids = sites
+            .iter()
+            .map(|(x, y)| {
+                network.add_node(TransportNode::new(
+                    Site::new(*x, *y),
+                    0.0,
+                    Stage::default(),
+                    false,
+                ))
+            })
+            .collect::<Vec<_>>();
+        paths.iter().for_each(|(start, end)| {
+            network.add_path(node_ids[*start], node_ids[*end]);
+        });
+        (network, node_ids)
+    }
+
+    fn count_dead_ends(network: &PathNetwork<TransportNode>) -> usize {
+        network
+            .nodes_iter()
+            .filter(|(node_id, _)| is_dead_end(network, *node_id))
+            .count()
+    }
+
+    #[test]
+    fn test_prune() {
+        // a junction with dead ends of 1, 2 and 3 paths.
+        let sites = [
+            (0.0, 0.0),
+            (-1.0, 0.0),
+            (1.0, 0.0),
+            (2.0, 0.0),
+            (0.0, 1.0),
+            (0.0, 2.0),
+            (0.0, 3.0),
+        ];
+        let paths = [(0, 1), (0, 2), (2, 3), (0, 4), (4, 5), (5, 6)];
+
+        let (mut network, node_ids) = create_network(&sites, &paths);
+        let rules = DeadEndRules::new(DeadEndTreatment::Prune { max_segments: 1 });
+        treat_dead_ends(&mut network, &RiverTerrain, &rules);
+        assert!(network.get_node(node_ids[1]).is_none());
+        assert_eq!(network.nodes_iter().count(), 6);
+
+        // the ratio of dead ends is already less than the target.
+        let (mut network, _) = create_network(&sites, &paths);
+        let rules =
+            DeadEndRules::new(DeadEndTreatment::Prune { max_segments: 3 }).target_ratio(0.5);
+        treat_dead_ends(&mut network, &RiverTerrain, &rules);
+        assert_eq!(network.nodes_iter().count(), 7);
+    }
+
+    #[test]
+    fn test_connect() {
+        // two dead ends facing each other.
+        let sites = [(0.0, 0.0), (1.0, 0.0), (3.0, 0.0), (2.0, 0.0)];
+        let paths = [(0, 1), (2, 3)];
+        let rules = DeadEndRules::new(DeadEndTreatment::Connect { max_length: 1.5 });
+
+        // a bridge is required but not allowed.
+        let (mut network, node_ids) = create_network(&sites, &paths);
+        treat_dead_ends(&mut network, &RiverTerrain, &rules);
+        assert!(!network.has_path(node_ids[1], node_ids[3]));
+        assert_eq!(count_dead_ends(&network), 4);
+
+        // connected with a bridge.
+        let (mut network, node_ids) = create_network(&sites, &paths);
+        treat_dead_ends(
+            &mut network,
+            &RiverTerrain,
+            &rules.clone().max_bridge_length(2.0),
+        );
+        let bridge = network
+            .neighbors_iter(node_ids[1])
+            .unwrap()
+            .find(|(_, node)| node.is_bridge)
+            .map(|(node_id, _)| node_id)
+            .unwrap();
+        assert!(network.has_path(bridge, node_ids[3]));
+        assert_eq!(count_dead_ends(&network), 2);
+
+        // the slope is not allowed.
+        let (mut network, _) = create_network(&sites, &paths);
+        treat_dead_ends(
+            &mut network,
+            &RiverTerrain,
+            &rules
+                .max_bridge_length(2.0)
+                .slope_limit(ElevationDiffLimit::AlwaysDeny),
+        );
+        assert_eq!(count_dead_ends(&network), 4);
+    }
+
+    #[test]
+    fn test_cul_de_sac() {
+        let (mut network, node_ids) = create_network(&[(0.0, 0.0), (0.0, 2.0)], &[(0, 1)]);
+        let rules = DeadEndRules::new(DeadEndTreatment::CulDeSac {
+            radius: 0.5,
+            node_count: 6,
+        })
+        .target_ratio(0.2);
+        treat_dead_ends(&mut network, &RiverTerrain, &rules);
+
+        // one of the dead ends is converted and the ratio becomes 1 / 7.
+        assert_eq!(network.nodes_iter().count(), 7);
+        assert_eq!(count_dead_ends(&network), 1);
+        let bulb_end = [node_ids[0], node_ids[1]]
+            .into_iter()
+            .find(|node_id| !is_dead_end(&network, *node_id))
+            .unwrap();
+        assert_eq!(network.neighbors_iter(bulb_end).unwrap().count(), 3);
+    }
+}
@@ -0,0 +1,39 @@
+use crate::core::{
+    container::path_network::{NodeId, PathNetwork},
+    geometry::{line_segment::LineSegment, site::Site},
+};
+
+use super::node::TransportNode;
+
+pub mod dead_end;
+pub mod roundabout;
+
+/// Check if the ring (closed polyline of `ring_sites` around `center`) collides with paths in the network.
+///
+/// Paths from `excluded` are ignored.
+fn ring_collides(
+    network: &PathNetwork<TransportNode>,
+    ring_sites: &[Site],
+    center: Site,
+    radius: f64,
+    excluded: NodeId,
+) -> bool {
+    let corner_0 = Site::new(center.x - radius, center.y - radius);
+    let corner_1 = Site::new(center.x + radius, center.y + radius);
+    network
+        .paths_touching_rect_iter(corner_0, corner_1)
+        .filter(|(start, end)| *start != excluded && *end != excluded)
+        .filter_map(|(start, end)| {
+            Some(LineSegment::new(
+                network.get_node(*start)?.site,
+                network.get_node(*end)?.site,
+            ))
+        })
+        .any(|path| {
+            (0..ring_sites.len()).any(|i| {
+                let ring_path =
+                    LineSegment::new(ring_sites[i], ring_sites[(i + 1) % ring_sites.len()]);
+                ring_path.get_intersection(&path).is_some()
+            })
+        })
+}
@@ -0,0 +1,341 @@
+use crate::{
+    core::{
+        container::path_network::{NodeId, PathNetwork},
+        geometry::angle::Angle,
+    },
+    transport::{
+        node::TransportNode,
+        params::{
+            numeric::Stage,
+            rules::{slope::SlopePath, ElevationDiffLimit},
+        },
+        traits::TerrainProvider,
+    },
+};
+
+use super::ring_collides;
+
+/// Rules to replace junctions with roundabouts.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RoundaboutRules {
+    /// Radius of the ring.
+    pub radius: f64,
+
+    /// Minimum number of nodes of the ring.
+    ///
+    /// The ring has at least one node for each incoming path,
+    /// and nodes are added until the angle between two adjacent nodes is smaller than `2 * PI / ring_node_count`.
+    pub ring_node_count: usize,
+
+    /// Minimum number of paths connected to the junction.
+    pub min_degree: usize,
+
+    /// Maximum stage of the junction. If `None`, junctions of all stages are qualified.
+    pub max_stage: Option<Stage>,
+
+    /// The limit of the elevation difference of the paths of the ring.
+    pub slope_limit: ElevationDiffLimit,
+}
+
+impl Default for RoundaboutRules {
+    fn default() -> Self {
+        Self {
+            radius: 0.0,
+            ring_node_count: 8,
+            min_degree: 3,
+            max_stage: None,
+            slope_limit: ElevationDiffLimit::AlwaysAllow,
+        }
+    }
+}
+
+impl RoundaboutRules {
+    /// Set the radius of the ring.
+    pub fn radius(mut self, radius: f64) -> Self {
+        self.radius = radius;
+        self
+    }
+
+    /// Set the minimum number of nodes of the ring.
+    pub fn ring_node_count(mut self, ring_node_count: usize) -> Self {
+        self.ring_node_count = ring_node_count;
+        self
+    }
+
+    /// Set the minimum number of paths connected to the junction.
+    pub fn min_degree(mut self, min_degree: usize) -> Self {
+        self.min_degree = min_degree;
+        self
+    }
+
+    /// Set the maximum stage of the junction.
+    pub fn max_stage(mut self, max_stage: Stage) -> Self {
+        self.max_stage = Some(max_stage);
+        self
+    }
+
+    /// Set the limit of the elevation difference of the paths of the ring.
+    pub fn slope_limit(mut self, slope_limit: ElevationDiffLimit) -> Self {
+        self.slope_limit = slope_limit;
+        self
+    }
+}
+
+/// Node of the ring to be created.
+struct RingNode {
+    node: TransportNode,
+    /// The node of the incoming path connected to this ring node.
+    incoming: Option<NodeId>,
+}
+
+/// Replace qualifying junctions in the network with roundabouts.
+///
+/// `predicate` receives the junction and its neighbors, and can reject the junction.
+/// Junctions whose ring doesn't fit the terrain slope or collides with other paths are left as they are.
+pub(crate) fn place_roundabouts<TP, F>(
+    network: &mut PathNetwork<TransportNode>,
+    terrain_provider: &TP,
+    rules: &RoundaboutRules,
+    predicate: F,
+) where
+    TP: TerrainProvider,
+    F: Fn(&TransportNode, &[TransportNode]) -> bool,
+{
+    let junctions = network
+        .nodes_iter()
+        .map(|(node_id, _)| node_id)
+        .collect::<Vec<_>>();
+
+    junctions.into_iter().for_each(|junction_id| {
+        if let Some(ring) = plan_ring(network, terrain_provider, rules, &predicate, junction_id) {
+            apply_ring(network, junction_id, ring);
+        }
+    });
+}
+
+/// Plan the ring of the roundabout replacing the junction.
+fn plan_ring<TP, F>(
+    network: &PathNetwork<TransportNode>,
+    terrain_provider: &TP,
+    rules: &RoundaboutRules,
+    predicate: &F,
+    junction_id: NodeId,
+) -> Option<Vec<RingNode>>
+where
+    TP: TerrainProvider,
+    F: Fn(&TransportNode, &[TransportNode]) -> bool,
+{
+    let junction = *network.get_node(junction_id)?;
+    let neighbors = network.neighbors_iter(junction_id)?.collect::<Vec<_>>();
+
+    if rules.radius <= 0.0 || neighbors.len() < rules.min_degree || junction.is_bridge {
+        return None;
+    }
+    if let Some(max_stage) = rules.max_stage {
+        if junction.stage > max_stage {
+            return None;
+        }
+    }
+    let neighbor_nodes = neighbors.iter().map(|(_, node)| **node).collect::<Vec<_>>();
+    if neighbor_nodes.iter().any(|node| node.is_bridge) || !predicate(&junction, &neighbor_nodes) {
+        return None;
+    }
+
+    // the ring must not contain other nodes.
+    let center = junction.site;
+    if network
+        .nodes_around_site_iter(center, rules.radius)
+        .any(|node_id| *node_id != junction_id)
+    {
+        return None;
+    }
+
+    // attach points of incoming paths sorted by angle.
+    let mut attached = neighbors
+        .iter()
+        .map(|(node_id, _)| *node_id)
+        .zip(neighbor_nodes.iter())
+        .map(|(node_id, node)| (center.get_angle(&node.site).radian(), Some(node_id)))
+        .collect::<Vec<_>>();
+    attached.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+    // fill the gaps between attach points.
+    let max_gap = 2.0 * std::f64::consts::PI / rules.ring_node_count.max(1) as f64;
+    let mut ring_angles = Vec::new();
+    for i in 0..attached.len() {
+        let (radian, incoming) = attached[i];
+        let radian_next = attached[(i + 1) % attached.len()].0;
+        let gap = if attached.len() == 1 {
+            2.0 * std::f64::consts::PI
+        } else {
+            (radian_next - radian).rem_euclid(2.0 * std::f64::consts::PI)
+        };
+        if gap < 1e-6 {
+            // two paths are overlapping.
+            return None;
+        }
+        let segments = (gap / max_gap).ceil() as usize;
+        ring_angles.push((radian, incoming));
+        (1..segments).for_each(|j| {
+            ring_angles.push((radian + gap * (j as f64) / (segments as f64), None));
+        });
+    }
+
+    let ring = ring_angles
+        .into_iter()
+        .map(|(radian, incoming)| {
+            let site = center.extend(Angle::new(radian), rules.radius);
+            let elevation = terrain_provider.get_elevation(&site)?;
+            Some(RingNode {
+                node: TransportNode::new(site, elevation, junction.stage, false),
+                incoming,
+            })
+        })
+        .collect::<Option<Vec<_>>>()?;
+
+    // check slopes of the ring and the incoming paths.
+    let check_slope = |a: &TransportNode, b: &TransportNode| {
+        rules.slope_limit.check_slope(&SlopePath::new(*a, *b))
+    };
+    for i in 0..ring.len() {
+        let (node, node_next) = (&ring[i].node, &ring[(i + 1) % ring.len()].node);
+        if !check_slope(node, node_next) {
+            return None;
+        }
+        if let Some(incoming) = ring[i].incoming {
+            if !check_slope(node, network.get_node(incoming)?) {
+                return None;
+            }
+        }
+    }
+
+    // check collisions with other paths.
+    let ring_sites = ring
+        .iter()
+        .map(|ring_node| ring_node.node.site)
+        .collect::<Vec<_>>();
+    if ring_collides(network, &ring_sites, center, rules.radius, junction_id) {
+        return None;
+    }
+
+    Some(ring)
+}
+
+/// Replace the junction with the ring.
+fn apply_ring(network: &mut PathNetwork<TransportNode>, junction_id: NodeId, ring: Vec<RingNode>) {
+    network.remove_node(junction_id);
+
+    let ring_ids = ring
+        .iter()
+        .map(|ring_node| network.add_node(ring_node.node))
+        .collect::<Vec<_>>();
+
+    for i in 0..ring.len() {
+        network.add_path(ring_ids[i], ring_ids[(i + 1) % ring.len()]);
+        if let Some(incoming) = ring[i].incoming {
+            network.add_path(ring_ids[i], incoming);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::core::geometry::site::Site;
+
+    use super::*;
+
+    struct FlatTerrain;
+
+    impl TerrainProvider for FlatTerrain {
+        fn get_elevation(&self, _: &Site) -> Option<f64> {
+            Some(0.0)
+        }
+    }
+
+    fn create_node(x: f64, y: f64) -> TransportNode {
+        TransportNode::new(Site::new(x, y), 0.0, Stage::default(), false)
+    }
+
+    fn create_crossroad() -> (PathNetwork<TransportNode>, NodeId, Vec<NodeId>) {
+        let mut network = PathNetwork::new();
+        let center = network.add_node(create_node(0.0, 0.0));
+        let arms = [(5.0, 0.0), (0.0, 5.0), (-5.0, 0.0), (0.0, -5.0)]
+            .iter()
+            .map(|(x, y)| network.add_node(create_node(*x, *y)))
+            .collect::<Vec<_>>();
+        arms.iter().for_each(|arm| {
+            network.add_path(center, *arm);
+        });
+        (network, center, arms)
+    }
+
+    #[test]
+    fn test_place_roundabout() {
+        let (mut network, center, arms) = create_crossroad();
+        let rules = RoundaboutRules::default().radius(1.0).ring_node_count(8);
+
+        place_roundabouts(&mut network, &FlatTerrain, &rules, |_, _| true);
+
+        assert!(network.get_node(center).is_none());
+        assert_eq!(network.nodes_iter().count(), 4 + 8);
+
+        // each arm is connected to a node on the ring.
+        arms.iter().for_each(|arm| {
+            let neighbors = network.neighbors_iter(*arm).unwrap().collect::<Vec<_>>();
+            assert_eq!(neighbors.len(), 1);
+            assert!((neighbors[0].1.site.distance(&Site::new(0.0, 0.0)) - 1.0).abs() < 1e-6);
+        });
+
+        // ring nodes are connected to two ring nodes (and an arm).
+        network
+            .nodes_iter()
+            .filter(|(node_id, _)| !arms.contains(node_id))
+            .for_each(|(node_id, _)| {
+                let degree = network.neighbors_iter(node_id).unwrap().count();
+                assert!(degree == 2 || degree == 3);
+            });
+    }
+
+    #[test]
+    fn test_roundabout_not_qualified() {
+        // degree
+        let (mut network, center, _) = create_crossroad();
+        let rules = RoundaboutRules::default().radius(1.0).min_degree(5);
+        place_roundabouts(&mut network, &FlatTerrain, &rules, |_, _| true);
+        assert!(network.get_node(center).is_some());
+
+        // predicate
+        let rules = RoundaboutRules::default().radius(1.0);
+        place_roundabouts(&mut network, &FlatTerrain, &rules, |_, _| false);
+        assert!(network.get_node(center).is_some());
+
+        // slope
+        let rules = RoundaboutRules::default()
+            .radius(1.0)
+            .slope_limit(ElevationDiffLimit::AlwaysDeny);
+        place_roundabouts(&mut network, &FlatTerrain, &rules, |_, _| true);
+        assert!(network.get_node(center).is_some());
+    }
+
+    #[test]
+    fn test_roundabout_collision() {
+        let (mut network, center, _) = create_crossroad();
+
+        // a path passing close to the junction
+        let node0 = network.add_node(create_node(-3.0, 0.5));
+        let node1 = network.add_node(create_node(-0.5, 3.0));
+        network.add_path(node0, node1);
+
+        let rules = RoundaboutRules::default().radius(1.0);
+        place_roundabouts(&mut network, &FlatTerrain, &rules, |_, _| true);
+        assert!(network.get_node(center).is_none());
+
+        let (mut network, center, _) = create_crossroad();
+        let node0 = network.add_node(create_node(-3.0, 0.5));
+        let node1 = network.add_node(create_node(-0.3, 0.6));
+        network.add_path(node0, node1);
+
+        place_roundabouts(&mut network, &FlatTerrain, &rules, |_, _| true);
+        assert!(network.get_node(center).is_some());
+    }
+}
@@ -0,0 +1,298 @@
+use std::ops::Range;
+
+use crate::core::geometry::site::Site;
+
+use super::traits::RandomF64Provider;
+
+/// Step of SplitMix64, which also mixes the bits of the values well enough to hash them.
+pub(crate) fn split_mix_64(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+/// Random values derived from the seed and the sites of a path.
+///
+/// The values don't depend on the order in which the paths are created.
+#[derive(Debug, Clone)]
+pub(crate) struct PathRandom {
+    state: u64,
+}
+
+impl PathRandom {
+    /// Create a generator for the path from `start` to `end`.
+    pub fn new(seed: u64, start: Site, end: Site) -> Self {
+        let mut state = seed;
+        for value in [start.x, start.y, end.x, end.y] {
+            state = split_mix_64(&mut state) ^ value.to_bits();
+        }
+        Self {
+            state: split_mix_64(&mut state),
+        }
+    }
+}
+
+impl RandomF64Provider for PathRandom {
+    /// Generate a value in `[0, 1)`.
+    fn gen_f64(&mut self) -> f64 {
+        unit_f64(split_mix_64(&mut self.state))
+    }
+}
+
+/// Convert the random bits to a value in `[0, 1)`.
+fn unit_f64(bits: u64) -> f64 {
+    (bits >> 11) as f64 / (1u64 << 53) as f64
+}
+
+/// Map the random bits to a value in the range.
+fn range_f64(range: Range<f64>, bits: u64) -> f64 {
+    let value = range.start + (range.end - range.start) * unit_f64(bits);
+    // the value may be rounded up to the end of the range.
+    if value < range.end {
+        value
+    } else {
+        range.start
+    }
+}
+
+/// PCG32 (XSH RR) generator.
+///
+/// The generator is small and fast, and has `2^63` independent streams.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pcg32 {
+    state: u64,
+    increment: u64,
+}
+
+impl Pcg32 {
+    const MULTIPLIER: u64 = 6364136223846793005;
+
+    /// Create a generator from the seed.
+    pub fn new(seed: u64) -> Self {
+        Self::with_stream(seed, 0)
+    }
+
+    /// Create a generator from the seed on the stream.
+    pub fn with_stream(seed: u64, stream: u64) -> Self {
+        let mut rng = Self {
+            state: 0,
+            increment: (stream << 1) | 1,
+        };
+        rng.step();
+        rng.state = rng.state.wrapping_add(seed);
+        rng.step();
+        rng
+    }
+
+    /// Restore the generator from the state returned by `state`.
+    ///
+    /// If the state is invalid (the increment is even), `None` is returned.
+    pub fn from_state(state: [u64; 2]) -> Option<Self> {
+        let [state, increment] = state;
+        (increment % 2 == 1).then_some(Self { state, increment })
+    }
+
+    /// Get the state of the generator to save it.
+    pub fn state(&self) -> [u64; 2] {
+        [self.state, self.increment]
+    }
+
+    fn step(&mut self) {
+        self.state = self
+            .state
+            .wrapping_mul(Self::MULTIPLIER)
+            .wrapping_add(self.increment);
+    }
+
+    /// Generate a random `u32`.
+    pub fn gen_u32(&mut self) -> u32 {
+        let state = self.state;
+        self.step();
+        let xorshifted = (((state >> 18) ^ state) >> 27) as u32;
+        xorshifted.rotate_right((state >> 59) as u32)
+    }
+
+    /// Generate a random `u64`.
+    pub fn gen_u64(&mut self) -> u64 {
+        ((self.gen_u32() as u64) << 32) | self.gen_u32() as u64
+    }
+
+    /// Generate a value in the range, excluding the end.
+    pub fn gen_range(&mut self, range: Range<f64>) -> f64 {
+        range_f64(range, self.gen_u64())
+    }
+
+    /// Split off a generator on another stream, seeded from this generator.
+    pub fn split(&mut self) -> Self {
+        let (seed, stream) = (self.gen_u64(), self.gen_u64());
+        Self::with_stream(seed, stream)
+    }
+}
+
+impl RandomF64Provider for Pcg32 {
+    /// Generate a value in `[0, 1)`.
+    fn gen_f64(&mut self) -> f64 {
+        unit_f64(self.gen_u64())
+    }
+}
+
+/// Xoshiro256++ generator.
+///
+/// The generator has a long period (`2^256 - 1`), and splits into non-overlapping sequences of `2^128` values.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Xoshiro256 {
+    state: [u64; 4],
+}
+
+impl Xoshiro256 {
+    /// Create a generator from the seed.
+    pub fn new(seed: u64) -> Self {
+        let mut seed = seed;
+        Self {
+            state: std::array::from_fn(|_| split_mix_64(&mut seed)),
+        }
+    }
+
+    /// Restore the generator from the state returned by `state`.
+    ///
+    /// If the state is invalid (all zero), `None` is returned.
+    pub fn from_state(state: [u64; 4]) -> Option<Self> {
+        (state != [0; 4]).then_some(Self { state })
+    }
+
+    /// Get the state of the generator to save it.
+    pub fn state(&self) -> [u64; 4] {
+        self.state
+    }
+
+    /// Generate a random `u64`.
+    pub fn gen_u64(&mut self) -> u64 {
+        let s = &mut self.state;
+        let result = s[0].wrapping_add(s[3]).rotate_left(23).wrapping_add(s[0]);
+        let t = s[1] << 17;
+        s[2] ^= s[0];
+        s[3] ^= s[1];
+        s[1] ^= s[2];
+        s[0] ^= s[3];
+        s[2] ^= t;
+        s[3] = s[3].rotate_left(45);
+        result
+    }
+
+    /// Generate a value in the range, excluding the end.
+    pub fn gen_range(&mut self, range: Range<f64>) -> f64 {
+        range_f64(range, self.gen_u64())
+    }
+
+    /// Split off a generator continuing from the current state,
+    /// and advance this generator by `2^128` values so that the sequences don't overlap.
+    pub fn split(&mut self) -> Self {
+        let split = self.clone();
+        self.jump();
+        split
+    }
+
+    /// Advance the generator by `2^128` values.
+    fn jump(&mut self) {
+        const JUMP: [u64; 4] = [
+            0x180e_c6d3_3cfd_0aba,
+            0xd5a6_1266_f0c9_392c,
+            0xa958_2618_e03f_c9aa,
+            0x39ab_dc45_29b1_661c,
+        ];
+        let mut state = [0; 4];
+        for jump in JUMP {
+            for bit in 0..64 {
+                if jump & (1 << bit) != 0 {
+                    state
+                        .iter_mut()
+                        .zip(self.state)
+                        .for_each(|(state, value)| *state ^= value);
+                }
+                self.gen_u64();
+            }
+        }
+        self.state = state;
+    }
+}
+
+impl RandomF64Provider for Xoshiro256 {
+    /// Generate a value in `[0, 1)`.
+    fn gen_f64(&mut self) -> f64 {
+        unit_f64(self.gen_u64())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_path_random() {
+        let (start, end) = (Site::new(0.0, 0.0), Site::new(1.0, 0.0));
+        let values = |seed: u64, end: Site| {
+            let mut random = PathRandom::new(seed, start, end);
+            (0..4).map(|_| random.gen_f64()).collect::<Vec<_>>()
+        };
+
+        assert_eq!(values(0, end), values(0, end));
+        assert_ne!(values(0, end), values(1, end));
+        assert_ne!(values(0, end), values(0, Site::new(1.0, 1e-9)));
+        assert!(values(0, end)
+            .iter()
+            .all(|value| (0.0..1.0).contains(value)));
+    }
+
+    #[test]
+    fn test_range_f64() {
+        // the product with the largest unit value is rounded up to the end.
+        let range = 1.0..1.0 + f64::EPSILON;
+        assert_eq!(range_f64(range.clone(), u64::MAX), 1.0);
+        assert_eq!(range_f64(-2.0..3.0, 0), -2.0);
+
+        let mut pcg32 = Pcg32::new(0);
+        let mut xoshiro256 = Xoshiro256::new(0);
+        assert!((0..100).all(|_| range.contains(&pcg32.gen_range(range.clone()))));
+        assert!((0..100).all(|_| range.contains(&xoshiro256.gen_range(range.clone()))));
+    }
+
+    #[test]
+    fn test_pcg32() {
+        // the reference values of `pcg32_srandom_r(rng, 42, 54)`.
+        let mut rng = Pcg32::with_stream(42, 54);
+        let values = (0..4).map(|_| rng.gen_u32()).collect::<Vec<_>>();
+        assert_eq!(values, vec![0xa15c02b7, 0x7b47f409, 0xba1d3330, 0x83d2f293]);
+
+        let saved = rng.state();
+        let expected = (0..4).map(|_| rng.gen_f64()).collect::<Vec<_>>();
+        let mut restored = Pcg32::from_state(saved).unwrap();
+        assert_eq!(
+            (0..4).map(|_| restored.gen_f64()).collect::<Vec<_>>(),
+            expected
+        );
+        assert_eq!(Pcg32::from_state([0, 2]), None);
+
+        let mut split = rng.split();
+        assert_ne!(split.gen_u64(), rng.gen_u64());
+        assert!((0..100).all(|_| (-2.0..3.0).contains(&rng.gen_range(-2.0..3.0))));
+    }
+
+    #[test]
+    fn test_xoshiro256() {
+        let mut rng = Xoshiro256::from_state([1, 2, 3, 4]).unwrap();
+        assert_eq!(rng.gen_u64(), 41943041);
+        assert_eq!(Xoshiro256::from_state([0; 4]), None);
+        assert_eq!(Xoshiro256::new(1), Xoshiro256::new(1));
+        assert_ne!(Xoshiro256::new(1), Xoshiro256::new(2));
+
+        // the split generator continues the sequence, and this generator jumps ahead.
+        let mut rng = Xoshiro256::new(0);
+        let mut copy = rng.clone();
+        let mut split = rng.split();
+        assert_eq!(split.gen_u64(), copy.gen_u64());
+        assert_ne!(rng.gen_u64(), copy.gen_u64());
+        assert!((0..100).all(|_| (0.0..1.0).contains(&rng.gen_f64())));
+    }
+}
@@ -0,0 +1,597 @@
+use std::collections::BTreeMap;
+
+use crate::core::{
+    container::path_network::{NodeId, PathNetwork},
+    geometry::{angle::Angle, polygon::Polygon, site::Site},
+};
+
+use super::{
+    builder::TransportBuilder,
+    node::TransportNode,
+    params::numeric::Stage,
+    random::{split_mix_64, Pcg32},
+    traits::{PathPrioritizator, TerrainProvider, TransportRulesProvider},
+};
+
+/// Margin of the boundary of each tile relative to the tile size.
+///
+/// Paths leaving a tile are clipped slightly outside of the tile,
+/// so that the crossing sites are inside the neighbor tile.
+const SEAM_MARGIN: f64 = 1e-6;
+
+/// Default stitch distance relative to the tile size.
+const DEFAULT_STITCH_DISTANCE: f64 = 0.05;
+
+/// Coordinate of a tile.
+///
+/// The tile `(x, y)` covers `[x * tile_size, (x + 1) * tile_size) x [y * tile_size, (y + 1) * tile_size)`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TileCoord {
+    pub x: i64,
+    pub y: i64,
+}
+
+impl TileCoord {
+    pub fn new(x: i64, y: i64) -> Self {
+        Self { x, y }
+    }
+
+    /// Get the tile containing the site.
+    pub fn from_site(site: &Site, tile_size: f64) -> Self {
+        Self::new(
+            (site.x / tile_size).floor() as i64,
+            (site.y / tile_size).floor() as i64,
+        )
+    }
+
+    /// Get the 4 tiles sharing an edge with the tile.
+    pub fn neighbors(&self) -> [TileCoord; 4] {
+        [
+            Self::new(self.x - 1, self.y),
+            Self::new(self.x + 1, self.y),
+            Self::new(self.x, self.y - 1),
+            Self::new(self.x, self.y + 1),
+        ]
+    }
+
+    /// Get the corners `(min, max)` of the tile.
+    fn bounds(&self, tile_size: f64) -> (Site, Site) {
+        (
+            Site::new(self.x as f64 * tile_size, self.y as f64 * tile_size),
+            Site::new(
+                (self.x + 1) as f64 * tile_size,
+                (self.y + 1) as f64 * tile_size,
+            ),
+        )
+    }
+}
+
+/// Path leaving a tile across its edge.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TileCrossing {
+    /// The site where the path is clipped, slightly inside the neighbor tile.
+    pub site: Site,
+    /// The direction of the path toward the neighbor tile.
+    pub heading: Angle,
+    /// The stage of the path.
+    pub stage: Stage,
+    /// The tile which the path enters.
+    pub to: TileCoord,
+}
+
+/// Network generated in a tile.
+#[derive(Debug, Clone)]
+pub struct Tile {
+    coord: TileCoord,
+    network: PathNetwork<TransportNode>,
+    crossings: Vec<TileCrossing>,
+}
+
+impl Tile {
+    pub fn coord(&self) -> TileCoord {
+        self.coord
+    }
+
+    pub fn network(&self) -> &PathNetwork<TransportNode> {
+        &self.network
+    }
+
+    /// Get the paths leaving the tile.
+    pub fn crossings(&self) -> &[TileCrossing] {
+        &self.crossings
+    }
+}
+
+type OriginFn<'a> = Box<dyn Fn(TileCoord, &mut Pcg32) -> Vec<(Site, f64)> + 'a>;
+
+/// Generator of networks divided into tiles, which are generated lazily.
+///
+/// Each tile is grown from its own origins and the paths crossing from the generated neighbor tiles.
+/// The randomness is derived from the seed and the paths (see `TransportBuilder::seed_per_path`),
+/// so the network of a tile only depends on the seed and the crossings of its neighbors at the time it's generated.
+/// Paths reaching an edge of a tile generated earlier are stitched to the nearest crossing of that tile in `network`.
+pub struct TiledGenerator<'a, RP, TP, PP>
+where
+    RP: TransportRulesProvider,
+    TP: TerrainProvider,
+    PP: PathPrioritizator,
+{
+    rules_provider: &'a RP,
+    terrain_provider: &'a TP,
+    path_prioritizator: &'a PP,
+    tile_size: f64,
+    seed: u64,
+    origins_per_tile: usize,
+    origins: Option<OriginFn<'a>>,
+    stitch_distance: f64,
+    tiles: BTreeMap<TileCoord, Tile>,
+}
+
+impl<'a, RP, TP, PP> TiledGenerator<'a, RP, TP, PP>
+where
+    RP: TransportRulesProvider,
+    TP: TerrainProvider,
+    PP: PathPrioritizator,
+{
+    /// Create a new generator of tiles with the size.
+    ///
+    /// The stitch distance is 5% of the tile size by default.
+    pub fn new(
+        rules_provider: &'a RP,
+        terrain_provider: &'a TP,
+        path_prioritizator: &'a PP,
+        tile_size: f64,
+        seed: u64,
+    ) -> Self {
+        Self {
+            rules_provider,
+            terrain_provider,
+            path_prioritizator,
+            tile_size,
+            seed,
+            origins_per_tile: 1,
+            origins: None,
+            stitch_distance: tile_size * DEFAULT_STITCH_DISTANCE,
+            tiles: BTreeMap::new(),
+        }
+    }
+
+    /// Set the number of origins placed randomly in each tile.
+    pub fn origins_per_tile(mut self, origins_per_tile: usize) -> Self {
+        self.origins_per_tile = origins_per_tile;
+        self
+    }
+
+    /// Set the function which places the origins `(site, angle_radian)` of each tile.
+    ///
+    /// The generator passed to the function is seeded by the seed and the tile.
+    /// Origins outside of the tile are ignored.
+    pub fn origins<F>(mut self, origins: F) -> Self
+    where
+        F: Fn(TileCoord, &mut Pcg32) -> Vec<(Site, f64)> + 'a,
+    {
+        self.origins = Some(Box::new(origins));
+        self
+    }
+
+    /// Set the maximum distance between the crossings stitched across an edge of tiles.
+    ///
+    /// This should be about the length of paths, so that only the paths heading for each other are stitched.
+    pub fn stitch_distance(mut self, stitch_distance: f64) -> Self {
+        self.stitch_distance = stitch_distance;
+        self
+    }
+
+    pub fn tile_size(&self) -> f64 {
+        self.tile_size
+    }
+
+    /// Get the generated tile.
+    pub fn get_tile(&self, coord: TileCoord) -> Option<&Tile> {
+        self.tiles.get(&coord)
+    }
+
+    /// Get the generated tiles.
+    pub fn tiles_iter(&self) -> impl Iterator<Item = &Tile> {
+        self.tiles.values()
+    }
+
+    /// Remove the tile to release the memory.
+    ///
+    /// If the tile is generated again, paths crossing from the tiles generated after it are continued.
+    pub fn remove_tile(&mut self, coord: TileCoord) -> Option<Tile> {
+        self.tiles.remove(&coord)
+    }
+
+    /// Get the random generator of the tile.
+    fn tile_rng(&self, coord: TileCoord) -> Pcg32 {
+        let mut state = self.seed;
+        state = split_mix_64(&mut state) ^ coord.x as u64;
+        state = split_mix_64(&mut state) ^ coord.y as u64;
+        Pcg32::with_stream(self.seed, split_mix_64(&mut state))
+    }
+
+    /// Generate the tile if it's not generated yet.
+    pub fn generate_tile(&mut self, coord: TileCoord) -> &Tile {
+        if !self.tiles.contains_key(&coord) {
+            let tile = self.create_tile(coord);
+            self.tiles.insert(coord, tile);
+        }
+        &self.tiles[&coord]
+    }
+
+    /// Generate the tiles within `radius` tiles around the site (e.g. the camera), from the nearest one.
+    ///
+    /// Returns the coordinates of the newly generated tiles.
+    pub fn generate_around(&mut self, site: Site, radius: i64) -> Vec<TileCoord> {
+        let center = TileCoord::from_site(&site, self.tile_size);
+        let mut coords = (-radius..=radius)
+            .flat_map(|dy| (-radius..=radius).map(move |dx| (dx, dy)))
+            .map(|(dx, dy)| TileCoord::new(center.x + dx, center.y + dy))
+            .filter(|coord| !self.tiles.contains_key(coord))
+            .collect::<Vec<_>>();
+        coords.sort_by_key(|coord| {
+            let (dx, dy) = (coord.x - center.x, coord.y - center.y);
+            (dx * dx + dy * dy, *coord)
+        });
+        for coord in &coords {
+            self.generate_tile(*coord);
+        }
+        coords
+    }
+
+    fn create_tile(&self, coord: TileCoord) -> Tile {
+        let (min, max) = coord.bounds(self.tile_size);
+        let margin = self.tile_size * SEAM_MARGIN;
+        let boundary = Polygon::new(vec![
+            Site::new(min.x - margin, min.y - margin),
+            Site::new(max.x + margin, min.y - margin),
+            Site::new(max.x + margin, max.y + margin),
+            Site::new(min.x - margin, max.y + margin),
+        ]);
+        let is_inside =
+            |site: &Site| (min.x..max.x).contains(&site.x) && (min.y..max.y).contains(&site.y);
+
+        let mut builder = TransportBuilder::new(
+            self.rules_provider,
+            self.terrain_provider,
+            self.path_prioritizator,
+        )
+        .set_boundary(boundary)
+        .seed_per_path(self.seed);
+
+        // continue the paths crossing from the generated neighbors.
+        for neighbor in coord.neighbors() {
+            let crossings = self
+                .tiles
+                .get(&neighbor)
+                .into_iter()
+                .flat_map(|tile| tile.crossings.iter())
+                .filter(|crossing| crossing.to == coord);
+            for crossing in crossings {
+                builder.insert_origin(crossing.site, crossing.heading, Some(crossing.stage), false);
+            }
+        }
+
+        let mut rng = self.tile_rng(coord);
+        let origins = if let Some(origins) = &self.origins {
+            origins(coord, &mut rng)
+        } else {
+            (0..self.origins_per_tile)
+                .map(|_| {
+                    let site = Site::new(rng.gen_range(min.x..max.x), rng.gen_range(min.y..max.y));
+                    (site, rng.gen_range(0.0..std::f64::consts::TAU))
+                })
+                .collect()
+        };
+        for (site, angle) in origins.into_iter().filter(|(site, _)| is_inside(site)) {
+            builder.insert_origin(site, Angle::new(angle), None, true);
+        }
+
+        let network = builder
+            .iterate_as_possible(&mut rng)
+            .snapshot()
+            .0
+            .unwrap_or_default();
+
+        // paths clipped at the boundary leave the tile.
+        let crossings = network
+            .nodes_iter()
+            .filter(|(_, node)| !is_inside(&node.site))
+            .filter_map(|(node_id, node)| {
+                let (_, neighbor) = network.neighbors_iter(node_id)?.next()?;
+                let excess = [
+                    (min.x - node.site.x, TileCoord::new(coord.x - 1, coord.y)),
+                    (node.site.x - max.x, TileCoord::new(coord.x + 1, coord.y)),
+                    (min.y - node.site.y, TileCoord::new(coord.x, coord.y - 1)),
+                    (node.site.y - max.y, TileCoord::new(coord.x, coord.y + 1)),
+                ];
+                let (_, to) = excess.into_iter().max_by(|(a, _), (b, _)| a.total_cmp(b))?;
+                Some(TileCrossing {
+                    site: node.site,
+                    heading: neighbor.site.get_angle(&node.site),
+                    stage: node.get_stage(),
+                    to,
+                })
+            })
+            .collect();
+
+        Tile {
+            coord,
+            network,
+            crossings,
+        }
+    }
+
+    /// Get the network of all generated tiles stitched at the edges.
+    ///
+    /// Paths continued in the neighbor tile are merged at the crossing sites.
+    /// Other paths reaching a generated neighbor are connected to the nearest crossing
+    /// from the neighbor within the stitch distance.
+    pub fn network(&self) -> PathNetwork<TransportNode> {
+        let mut network = PathNetwork::new();
+        let find_or_add = |network: &mut PathNetwork<TransportNode>, node: &TransportNode| {
+            let existing = network
+                .nodes_around_site_iter(node.site, 0.0)
+                .next()
+                .copied();
+            existing.unwrap_or_else(|| network.add_node(*node))
+        };
+
+        for tile in self.tiles.values() {
+            let id_map = tile
+                .network
+                .nodes_iter()
+                .map(|(node_id, node)| (node_id, find_or_add(&mut network, node)))
+                .collect::<BTreeMap<NodeId, NodeId>>();
+            for (node_id, _) in tile.network.nodes_iter() {
+                for (neighbor_id, _) in tile.network.neighbors_iter(node_id).into_iter().flatten() {
+                    if node_id < neighbor_id {
+                        network.add_path(id_map[&node_id], id_map[&neighbor_id]);
+                    }
+                }
+            }
+        }
+
+        for tile in self.tiles.values() {
+            for crossing in &tile.crossings {
+                let neighbor = if let Some(neighbor) = self.tiles.get(&crossing.to) {
+                    neighbor
+                } else {
+                    continue;
+                };
+                // the path is continued in the neighbor tile.
+                if neighbor
+                    .network
+                    .nodes_around_site_iter(crossing.site, 0.0)
+                    .next()
+                    .is_some()
+                {
+                    continue;
+                }
+                let nearest = neighbor
+                    .crossings
+                    .iter()
+                    .filter(|other| other.to == tile.coord)
+                    .map(|other| (other.site, other.site.distance(&crossing.site)))
+                    .filter(|(_, distance)| *distance <= self.stitch_distance)
+                    .min_by(|(_, a), (_, b)| a.total_cmp(b));
+                if let Some((site, _)) = nearest {
+                    let start = network
+                        .nodes_around_site_iter(crossing.site, 0.0)
+                        .next()
+                        .copied();
+                    let end = network.nodes_around_site_iter(site, 0.0).next().copied();
+                    if let (Some(start), Some(end)) = (start, end) {
+                        network.add_path(start, end);
+                    }
+                }
+            }
+        }
+
+        network
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::transport::{
+        combinator::{FnPathPrioritizator, FnRulesProvider, FnTerrainProvider},
+        context::GrowthContext,
+        params::{
+            priority::PathPrioritizationFactors,
+            rules::{branch::BranchRules, direction::PathDirectionRules, TransportRules},
+        },
+    };
+
+    use super::*;
+
+    fn generate<'a, RP, TP, PP>(
+        rules_provider: &'a RP,
+        terrain_provider: &'a TP,
+        path_prioritizator: &'a PP,
+        coords: &[TileCoord],
+    ) -> TiledGenerator<'a, RP, TP, PP>
+    where
+        RP: TransportRulesProvider,
+        TP: TerrainProvider,
+        PP: PathPrioritizator,
+    {
+        let mut generator =
+            TiledGenerator::new(rules_provider, terrain_provider, path_prioritizator, 6.0, 3)
+                .origins(|coord, _| {
+                    // a horizontal path in the middle of the first tile.
+                    if coord == TileCoord::new(0, 0) {
+                        vec![(Site::new(3.0, 3.0), std::f64::consts::FRAC_PI_2)]
+                    } else {
+                        vec![]
+                    }
+                })
+                .stitch_distance(2.0);
+        for coord in coords {
+            generator.generate_tile(*coord);
+        }
+        generator
+    }
+
+    #[test]
+    fn test_paths_continue_across_tiles() {
+        let rules_provider = FnRulesProvider(|_: &GrowthContext| {
+            Some(
+                TransportRules::default()
+                    .path_normal_length(1.0)
+                    .path_extra_length_for_intersection(0.3)
+                    .path_direction_rules(PathDirectionRules {
+                        max_radian: 0.2,
+                        comparison_step: 3,
+                        ..Default::default()
+                    })
+                    .branch_rules(BranchRules {
+                        branch_density: 0.2,
+                        staging_probability: 0.0,
+                    }),
+            )
+        });
+        let terrain_provider = FnTerrainProvider(|_: &Site| Some(0.0));
+        let path_prioritizator =
+            FnPathPrioritizator(|factors: PathPrioritizationFactors| Some(-factors.site_end.x));
+
+        let coords = [TileCoord::new(0, 0), TileCoord::new(1, 0)];
+        let generator = generate(
+            &rules_provider,
+            &terrain_provider,
+            &path_prioritizator,
+            &coords,
+        );
+
+        let first = generator.get_tile(coords[0]).unwrap();
+        let second = generator.get_tile(coords[1]).unwrap();
+        let crossings = first
+            .crossings()
+            .iter()
+            .filter(|crossing| crossing.to == coords[1])
+            .collect::<Vec<_>>();
+        assert!(!crossings.is_empty());
+        // the paths crossing the edge are continued in the second tile.
+        for crossing in crossings {
+            let node_id = *second
+                .network()
+                .nodes_around_site_iter(crossing.site, 0.0)
+                .next()
+                .unwrap();
+            assert!(second.network().neighbors_iter(node_id).unwrap().count() > 0);
+        }
+
+        // the crossing nodes are merged in the stitched network.
+        let node_count = |network: &PathNetwork<TransportNode>| network.nodes_iter().count();
+        let stitched = generator.network();
+        assert!(node_count(&stitched) < node_count(first.network()) + node_count(second.network()));
+
+        // tiles are generated deterministically.
+        let regenerated = generate(
+            &rules_provider,
+            &terrain_provider,
+            &path_prioritizator,
+            &coords,
+        );
+        let sites = |network: &PathNetwork<TransportNode>| {
+            network
+                .nodes_iter()
+                .map(|(_, node)| node.site)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(sites(&regenerated.network()), sites(&stitched));
+    }
+
+    #[test]
+    fn test_generate_around() {
+        let rules_provider = FnRulesProvider(|_: &GrowthContext| None);
+        let terrain_provider = FnTerrainProvider(|_: &Site| Some(0.0));
+        let path_prioritizator = FnPathPrioritizator(|_: PathPrioritizationFactors| Some(0.0));
+        let mut generator = TiledGenerator::new(
+            &rules_provider,
+            &terrain_provider,
+            &path_prioritizator,
+            10.0,
+            0,
+        );
+
+        let coords = generator.generate_around(Site::new(5.0, -5.0), 1);
+        assert_eq!(coords.len(), 9);
+        assert_eq!(coords[0], TileCoord::new(0, -1));
+        // generated tiles are not generated again.
+        assert_eq!(generator.generate_around(Site::new(15.0, -5.0), 1).len(), 3);
+        assert!(generator.remove_tile(TileCoord::new(0, -1)).is_some());
+        assert_eq!(generator.tiles_iter().count(), 11);
+    }
+
+    #[test]
+    fn test_independent_paths_are_stitched() {
+        // straight paths without branches.
+        let rules_provider = FnRulesProvider(|_: &GrowthContext| {
+            Some(TransportRules::default().path_normal_length(1.0))
+        });
+        let terrain_provider = FnTerrainProvider(|_: &Site| Some(0.0));
+        let path_prioritizator = FnPathPrioritizator(|_: PathPrioritizationFactors| Some(0.0));
+        let coords = [TileCoord::new(0, 0), TileCoord::new(1, 0)];
+        let generate = |stitch_distance: Option<f64>| {
+            let generator = TiledGenerator::new(
+                &rules_provider,
+                &terrain_provider,
+                &path_prioritizator,
+                6.0,
+                0,
+            )
+            .origins(|coord, _| {
+                // horizontal paths in both tiles, slightly apart from each other.
+                let site = if coord == TileCoord::new(0, 0) {
+                    Site::new(3.0, 3.0)
+                } else {
+                    Site::new(7.5, 3.2)
+                };
+                vec![(site, std::f64::consts::FRAC_PI_2)]
+            });
+            let mut generator = if let Some(stitch_distance) = stitch_distance {
+                generator.stitch_distance(stitch_distance)
+            } else {
+                generator
+            };
+            for coord in coords {
+                generator.generate_tile(coord);
+            }
+            generator
+        };
+
+        // the path started in the second tile reaches the first tile, which is already generated.
+        let generator = generate(None);
+        let crossing = generator
+            .get_tile(coords[1])
+            .unwrap()
+            .crossings()
+            .iter()
+            .find(|crossing| crossing.to == coords[0])
+            .copied()
+            .unwrap();
+        assert!(generator
+            .get_tile(coords[0])
+            .unwrap()
+            .network()
+            .nodes_around_site_iter(crossing.site, 0.0)
+            .next()
+            .is_none());
+
+        // it's stitched to the path from the first tile by default.
+        let is_stitched = |network: &PathNetwork<TransportNode>| {
+            let node_id = *network
+                .nodes_around_site_iter(crossing.site, 0.0)
+                .next()
+                .unwrap();
+            network
+                .neighbors_iter(node_id)
+                .unwrap()
+                .any(|(_, neighbor)| (neighbor.site.y - 3.0).abs() < 1e-9)
+        };
+        assert!(is_stitched(&generator.network()));
+        assert!(!is_stitched(&generate(Some(0.0)).network()));
+    }
+}
@@ -1,25 +1,106 @@
 use crate::core::geometry::site::Site;
 
-use super::params::{
-    metrics::PathMetrics, numeric::Stage, priority::PathPrioritizationFactors,
-    rules::TransportRules,
+use super::{
+    context::GrowthContext,
+    params::{
+        metrics::{GrowthMetrics, PathMetrics},
+        numeric::Stage,
+        priority::PathPrioritizationFactors,
+        rules::TransportRules,
+    },
 };
 
 /// Provider of transport rules.
-pub trait TransportRulesProvider {
+pub trait TransportRulesProvider<M = PathMetrics>
+where
+    M: GrowthMetrics,
+{
+    /// Get the rules for the path to be extended in the context.
+    fn get_rules(&self, context: &GrowthContext<M>) -> Option<TransportRules>;
+}
+
+/// Providers are shared by reference (e.g. in the combinators of `transport::combinator`).
+impl<M, P> TransportRulesProvider<M> for &P
+where
+    M: GrowthMetrics,
+    P: TransportRulesProvider<M> + ?Sized,
+{
+    fn get_rules(&self, context: &GrowthContext<M>) -> Option<TransportRules> {
+        (**self).get_rules(context)
+    }
+}
+
+/// Provider of transport rules only from the site, the stage and the metrics of the path.
+///
+/// This is the former interface of `TransportRulesProvider`.
+/// Wrap the provider with `SiteRulesAdapter` to use it as `TransportRulesProvider`.
+pub trait SiteRulesProvider {
     fn get_rules(&self, site: &Site, stage: Stage, metrics: &PathMetrics)
         -> Option<TransportRules>;
 }
 
+/// Adapter to use `SiteRulesProvider` as `TransportRulesProvider`.
+pub struct SiteRulesAdapter<P>(pub P)
+where
+    P: SiteRulesProvider;
+
+impl<P> TransportRulesProvider for SiteRulesAdapter<P>
+where
+    P: SiteRulesProvider,
+{
+    fn get_rules(&self, context: &GrowthContext) -> Option<TransportRules> {
+        self.0
+            .get_rules(&context.node.site, context.stage, context.metrics)
+    }
+}
+
 /// Provider of terrain elevation.
 pub trait TerrainProvider {
     fn get_elevation(&self, site: &Site) -> Option<f64>;
+
+    /// Get the gradient of the elevation `(dz/dx, dz/dy)` at the site.
+    ///
+    /// The default implementation calculates central differences with the distance `delta`.
+    fn get_gradient(&self, site: &Site, delta: f64) -> Option<(f64, f64)> {
+        let x0 = self.get_elevation(&Site::new(site.x - delta, site.y))?;
+        let x1 = self.get_elevation(&Site::new(site.x + delta, site.y))?;
+        let y0 = self.get_elevation(&Site::new(site.x, site.y - delta))?;
+        let y1 = self.get_elevation(&Site::new(site.x, site.y + delta))?;
+        Some(((x1 - x0) / (2.0 * delta), (y1 - y0) / (2.0 * delta)))
+    }
+
+    /// Get the elevations of the sites at once.
+    ///
+    /// The default implementation calls `get_elevation` for each site.
+    /// Override this to evaluate the sites in a vectorized way.
+    fn get_elevations(&self, sites: &[Site]) -> Vec<Option<f64>> {
+        sites.iter().map(|site| self.get_elevation(site)).collect()
+    }
 }
 
 /// Prioritizator of path.
-pub trait PathPrioritizator {
+pub trait PathPrioritizator<M = PathMetrics>
+where
+    M: GrowthMetrics,
+{
     /// Calculate the priority of the path from the start node and the expected path.
-    fn prioritize(&self, factors: PathPrioritizationFactors) -> Option<f64>;
+    fn prioritize(&self, factors: PathPrioritizationFactors<M>) -> Option<f64>;
+
+    /// Calculate the priorities of the paths at once.
+    ///
+    /// The default implementation calls `prioritize` for each path.
+    fn prioritize_many(&self, factors: &[PathPrioritizationFactors<M>]) -> Vec<Option<f64>> {
+        factors
+            .iter()
+            .map(|factors| self.prioritize(*factors))
+            .collect()
+    }
+}
+
+/// Provider of the maximum area of blocks (regions enclosed by paths).
+pub trait BlockAreaProvider {
+    /// Get the maximum area of the block around the site.
+    fn get_max_block_area(&self, site: &Site) -> Option<f64>;
 }
 
 /// Provider of random f64 values.